@@ -0,0 +1,48 @@
+//! Compares parsing numeric values straight from their bytes with collecting them
+//! into a string first, as values were parsed before.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use strp::{try_scan, TryParse, TryParseError};
+
+/// Parses a value by collecting its bytes into a string first.
+fn collected<T: std::str::FromStr>(
+    iter: &mut impl Iterator<Item = u8>,
+) -> Result<T, TryParseError<T::Err>> {
+    let vec = iter.collect::<Vec<u8>>();
+    let str = std::str::from_utf8(&vec).or(Err(TryParseError::InvalidUtf8String))?;
+    Ok(str.parse()?)
+}
+
+fn numeric(c: &mut Criterion) {
+    let mut group = c.benchmark_group("numeric");
+    group.bench_function("u32/direct", |b| {
+        b.iter(|| u32::try_parse(&mut black_box("4294967295").bytes()))
+    });
+    group.bench_function("u32/collected", |b| {
+        b.iter(|| collected::<u32>(&mut black_box("4294967295").bytes()))
+    });
+    group.bench_function("i64/direct", |b| {
+        b.iter(|| i64::try_parse(&mut black_box("-9223372036854775808").bytes()))
+    });
+    group.bench_function("i64/collected", |b| {
+        b.iter(|| collected::<i64>(&mut black_box("-9223372036854775808").bytes()))
+    });
+    group.bench_function("f64/direct", |b| {
+        b.iter(|| f64::try_parse(&mut black_box("-1234.5678e-3").bytes()))
+    });
+    group.bench_function("f64/collected", |b| {
+        b.iter(|| collected::<f64>(&mut black_box("-1234.5678e-3").bytes()))
+    });
+    group.bench_function("scan", |b| {
+        b.iter(|| {
+            let line = black_box("move 12 -34 to 5.5, 0.25 with ff");
+            let values: Result<(u32, i32, f32, f64, u8), _> =
+                try_scan!(line => "move {} {} to {}, {} with {:x}");
+            values
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, numeric);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+//! Compares matching a line against a `PatternSet` with trying each of its matching
+//! strings in turn, for sets of increasing size.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use strp::set::PatternSet;
+
+/// Matching strings shaped like the message formats of a plugin.
+fn patterns(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| match i % 4 {
+            0 => format!("event{i}: {{}} joined {{}}"),
+            1 => format!("event{i}: {{}} left{{[ early]}}"),
+            2 => format!("[{i}] {{(info|warn)}}: {{:+}}"),
+            _ => format!("metric.{i} = {{:digit}}"),
+        })
+        .collect()
+}
+
+fn pattern_set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pattern_set");
+    for count in [10, 100, 1000] {
+        let patterns = patterns(count);
+        let set = PatternSet::new(&patterns).unwrap();
+        let each = Vec::from_iter(patterns.iter().map(|p| PatternSet::new([p]).unwrap()));
+        let line = format!("event{}: alice joined #general", count - 4);
+
+        group.bench_with_input(BenchmarkId::new("set", count), &line, |b, line| {
+            b.iter(|| set.matches(black_box(line)).next())
+        });
+        group.bench_with_input(BenchmarkId::new("in_turn", count), &line, |b, line| {
+            b.iter(|| each.iter().position(|set| set.is_match(black_box(line))))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, pattern_set);
+criterion_main!(benches);
@@ -0,0 +1,298 @@
+//! Core crate for strp crate, holding the matcher which splits a source string into
+//! the values of a matching string. With the `alloc` feature it also holds the parser
+//! of the matching string syntax, shared by the macros and `strp::set::PatternSet`.
+
+#![no_std]
+#![warn(missing_docs)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+pub mod syntax;
+
+use core::ops::Range;
+
+/// A piece of the string preceding a matched value, or trailing the last one.
+#[derive(Clone, Copy)]
+pub enum Lit<'a> {
+    /// Text which has to be matched.
+    Str(&'a str),
+    /// Text which is matched if present.
+    Opt(&'a str),
+    /// Alternatives of which the first to match is used.
+    Alt(&'a [&'a str]),
+}
+
+/// The bytes a matched value may consist of.
+#[derive(Clone, Copy)]
+pub struct Class<'a> {
+    /// Whether the value consists of the bytes outside of `ranges` instead.
+    pub negated: bool,
+    /// Inclusive ranges of bytes.
+    pub ranges: &'a [(u8, u8)],
+}
+
+impl Class<'_> {
+    /// Whether `b` is one of the bytes.
+    #[inline(always)]
+    pub fn contains(&self, b: u8) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= b && b <= hi) != self.negated
+    }
+}
+
+/// The string preceding a matched value, or trailing the last one.
+pub struct Segment<'a> {
+    /// The string as written in the matching string.
+    pub m_str: &'a str,
+    /// The pieces the string consists of.
+    pub lits: &'a [Lit<'a>],
+    /// Bytes ending the value which follows the string. If empty, the value
+    /// consumes the rest of the source.
+    pub delims: &'a [u8],
+    /// Whether the value which follows the string is matched without being parsed.
+    pub skip: bool,
+    /// Whether the value which follows the string ends at the last of `delims`
+    /// where the rest matches, instead of the first.
+    pub greedy: bool,
+    /// Bytes the value which follows the string consists of. If set, the value
+    /// ends at the first other byte instead of at `delims`.
+    pub class: Option<Class<'a>>,
+}
+
+/// Matches `lits` against the start of `iter`, advancing it past the match if
+/// successful. If `end` is set the match also has to reach the end of `iter`.
+///
+/// Optional text is matched if possible, and alternatives are tried in order,
+/// backtracking whenever the rest of `lits` fails to match.
+pub fn match_lits<I: Iterator<Item = u8> + Clone>(iter: &mut I, lits: &[Lit], end: bool) -> bool {
+    let Some((lit, rest)) = lits.split_first() else {
+        return !end || iter.clone().next().is_none();
+    };
+    let mut attempt = |m_str: &str| {
+        let mut cur = iter.clone();
+        if m_str.bytes().all(|b| cur.next() == Some(b)) && match_lits(&mut cur, rest, end) {
+            *iter = cur;
+            true
+        } else {
+            false
+        }
+    };
+    match lit {
+        Lit::Str(m_str) => attempt(m_str),
+        Lit::Opt(m_str) => attempt(m_str) || attempt(""),
+        Lit::Alt(alts) => alts.iter().any(|m_str| attempt(m_str)),
+    }
+}
+
+/// The number of slots of `Memo` without the `alloc` feature.
+#[cfg(not(feature = "alloc"))]
+const MEMO_LEN: usize = 64;
+
+/// An empty slot of `Memo`, which no pair is equal to since segment indices are
+/// far smaller.
+const VACANT: (usize, usize) = (usize::MAX, 0);
+
+/// A hash set of segment indices and positions, used to remember which segments
+/// are known not to match where. Only the pairs which were inserted are stored,
+/// so its size doesn't depend on the length of the source.
+///
+/// Without the `alloc` feature at most `MEMO_LEN / 2` pairs are remembered, which
+/// makes matching some long sources slower but not wrong.
+#[cfg_attr(feature = "alloc", derive(Default))]
+pub struct Memo {
+    /// Always empty or a power of two in length, and at least twice `len`.
+    #[cfg(feature = "alloc")]
+    slots: alloc::vec::Vec<(usize, usize)>,
+    #[cfg(not(feature = "alloc"))]
+    slots: [(usize, usize); MEMO_LEN],
+    len: usize,
+}
+
+#[cfg(not(feature = "alloc"))]
+impl Default for Memo {
+    fn default() -> Self {
+        Self {
+            slots: [VACANT; MEMO_LEN],
+            len: 0,
+        }
+    }
+}
+
+impl Memo {
+    /// Whether the pair of `i` and `pos` is remembered.
+    #[inline(always)]
+    pub fn contains(&self, i: usize, pos: usize) -> bool {
+        self.len != 0 && self.slots[Self::slot(&self.slots, (i, pos))] == (i, pos)
+    }
+
+    /// Remembers the pair of `i` and `pos`, unless the set is full.
+    pub fn insert(&mut self, i: usize, pos: usize) {
+        if 2 * (self.len + 1) > self.slots.len() {
+            #[cfg(feature = "alloc")]
+            self.grow();
+            #[cfg(not(feature = "alloc"))]
+            return;
+        }
+        let slot = Self::slot(&self.slots, (i, pos));
+        if self.slots[slot] == VACANT {
+            self.slots[slot] = (i, pos);
+            self.len += 1;
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn grow(&mut self) {
+        let len = (2 * self.slots.len()).max(16);
+        let slots = core::mem::replace(&mut self.slots, alloc::vec![VACANT; len]);
+        for pair in slots.into_iter().filter(|&pair| pair != VACANT) {
+            let slot = Self::slot(&self.slots, pair);
+            self.slots[slot] = pair;
+        }
+    }
+
+    /// Finds the slot holding `pair`, or the vacant slot it would be inserted in.
+    #[inline(always)]
+    fn slot(slots: &[(usize, usize)], pair: (usize, usize)) -> usize {
+        let mask = slots.len() - 1;
+        let hash =
+            (pair.1 ^ pair.0.rotate_right(8)).wrapping_mul(0x9e37_79b9_7f4a_7c15_u64 as usize);
+        let mut slot = hash >> (usize::BITS - mask.count_ones());
+        while slots[slot] != pair && slots[slot] != VACANT {
+            slot = (slot + 1) & mask;
+        }
+        slot
+    }
+}
+
+/// Why a source failed to match.
+#[derive(Debug, PartialEq)]
+pub enum Failure<'a, E> {
+    /// The string which was expected, and the byte offset in the source where it
+    /// was expected.
+    Mismatch(&'a str, usize),
+    /// A value failed to parse.
+    Value(E),
+}
+
+/// Searches for where each matched value ends, backtracking whenever the rest
+/// of the matching string fails to match or a value fails to parse.
+struct Matcher<'m, 'a, E> {
+    slice: &'m [u8],
+    segments: &'m [Segment<'a>],
+    tail: &'m Segment<'a>,
+    end: bool,
+    /// The pairs of `i` and `pos` where the segments from the `i`th onwards are
+    /// known not to match.
+    failed: Memo,
+    /// The first error which occured alongside the index of the segment it occured
+    /// in, which is the one returned if nothing matches.
+    err: Option<(usize, Failure<'a, E>)>,
+}
+
+impl<'a, E> Matcher<'_, 'a, E> {
+    fn fail(&mut self, i: usize, err: Failure<'a, E>) {
+        self.err.get_or_insert((i, err));
+    }
+
+    /// Matches the segments from the `i`th onwards at `pos`, with `value` being the
+    /// index of the next parsed value, returning where the trailing string's match
+    /// ends.
+    fn search(
+        &mut self,
+        i: usize,
+        value: usize,
+        pos: usize,
+        parse: &mut impl FnMut(usize, Range<usize>) -> Result<(), E>,
+    ) -> Option<usize> {
+        let slice = self.slice;
+        let rest = &slice[pos..];
+        let Some(segment) = self.segments.get(i) else {
+            let mut iter = rest.iter().copied();
+            if match_lits(&mut iter, self.tail.lits, self.end) {
+                return Some(slice.len() - iter.len());
+            }
+            self.fail(i, Failure::Mismatch(self.tail.m_str, pos));
+            return None;
+        };
+        if self.failed.contains(i, pos) {
+            return None;
+        }
+        let mut iter = rest.iter().copied();
+        let found = if !match_lits(&mut iter, segment.lits, false) {
+            self.fail(i, Failure::Mismatch(segment.m_str, pos));
+            None
+        } else {
+            let start = slice.len() - iter.len();
+            let bytes = &slice[start..];
+            let mut attempt = |len: usize| {
+                // Whether what follows the value matches doesn't depend on the
+                // value, so there's no need to parse it if it's known not to.
+                if self.failed.contains(i + 1, start + len) {
+                    return None;
+                }
+                if segment.skip {
+                    return self.search(i + 1, value, start + len, parse);
+                }
+                match parse(value, start..start + len) {
+                    Ok(()) => self.search(i + 1, value + 1, start + len, parse),
+                    Err(e) => {
+                        self.fail(i, Failure::Value(e));
+                        None
+                    }
+                }
+            };
+            if let Some(class) = segment.class {
+                attempt(
+                    bytes
+                        .iter()
+                        .position(|b| !class.contains(*b))
+                        .unwrap_or(bytes.len()),
+                )
+            } else if segment.delims.is_empty() {
+                attempt(bytes.len())
+            } else {
+                let ends = (0..bytes.len())
+                    .filter(|&len| segment.delims.contains(&bytes[len]))
+                    .chain([bytes.len()]);
+                if segment.greedy {
+                    ends.rev().find_map(attempt)
+                } else {
+                    { ends }.find_map(attempt)
+                }
+            }
+        };
+        if found.is_none() {
+            self.failed.insert(i, pos);
+        }
+        found
+    }
+}
+
+/// Matches `segments` followed by `tail` against `slice`, calling `parse` with the
+/// index and range in `slice` of each value as it's found, except for skipped values.
+/// If `end` is set the match has to reach the end of `slice`.
+///
+/// Returns where the match ends, or the first error which occured if no way of
+/// splitting `slice` into values matches, alongside the index of the segment it
+/// occured in, being `segments.len()` for the trailing string.
+pub fn match_values<'a, E>(
+    slice: &[u8],
+    segments: &[Segment<'a>],
+    tail: &Segment<'a>,
+    end: bool,
+    mut parse: impl FnMut(usize, Range<usize>) -> Result<(), E>,
+) -> Result<usize, (usize, Failure<'a, E>)> {
+    let mut matcher = Matcher {
+        slice,
+        segments,
+        tail,
+        end,
+        failed: Memo::default(),
+        err: None,
+    };
+    match matcher.search(0, 0, 0, &mut parse) {
+        Some(pos) => Ok(pos),
+        None => Err(matcher.err.expect("a failed match records its error")),
+    }
+}
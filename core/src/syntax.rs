@@ -0,0 +1,551 @@
+//! The syntax of matching strings, parsed by the macros while expanding, and by
+//! `strp::set::PatternSet` at runtime.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, iter::Peekable, str::Chars};
+
+/// An invalid matching string, holding why it's invalid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error(pub String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Returns an error with a formatted message.
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err(Error(format!($($arg)*)))
+    };
+}
+
+/// Returns an error with a formatted message unless `cond` holds.
+macro_rules! ensure {
+    ($cond:expr, $($arg:tt)*) => {
+        if !$cond {
+            bail!($($arg)*);
+        }
+    };
+}
+
+/// A piece of the string preceding a value, or trailing the last one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lit {
+    /// Text which has to be matched.
+    Str(String),
+    /// Text which is matched if present, written as `{[text]}`.
+    Opt(String),
+    /// Alternatives of which the first to match is used, written as `{(a|b)}`.
+    Alt(Vec<String>),
+}
+
+/// The bytes a value may consist of, written as `{:[a-z]}` or `{:alpha}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Class {
+    /// Whether the value consists of the bytes outside of `ranges` instead.
+    pub negated: bool,
+    /// Inclusive ranges of bytes.
+    pub ranges: Vec<(u8, u8)>,
+}
+
+impl Class {
+    /// The class written as a name such as `alpha` or `digit`, if `name` is one.
+    pub fn named(name: &str) -> Option<Self> {
+        let ranges = match name {
+            "alpha" => vec![(b'A', b'Z'), (b'a', b'z')],
+            "alnum" => vec![(b'0', b'9'), (b'A', b'Z'), (b'a', b'z')],
+            "digit" => vec![(b'0', b'9')],
+            "lower" => vec![(b'a', b'z')],
+            "upper" => vec![(b'A', b'Z')],
+            "space" => vec![(b'\t', b'\r'), (b' ', b' ')],
+            "word" => vec![(b'0', b'9'), (b'A', b'Z'), (b'_', b'_'), (b'a', b'z')],
+            _ => return None,
+        };
+        Some(Self {
+            negated: false,
+            ranges,
+        })
+    }
+
+    /// The class as it's matched through.
+    pub fn borrowed(&self) -> crate::Class<'_> {
+        crate::Class {
+            negated: self.negated,
+            ranges: &self.ranges,
+        }
+    }
+}
+
+/// The string preceding a value, or trailing the last one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Segment {
+    /// The string as written in the matching string, used in errors.
+    pub m_str: String,
+    /// The pieces the string consists of.
+    pub lits: Vec<Lit>,
+}
+
+impl Segment {
+    fn push(&mut self, c: char) {
+        self.m_str.push(c);
+        if let Some(Lit::Str(m_str)) = self.lits.last_mut() {
+            m_str.push(c);
+        } else {
+            self.lits.push(Lit::Str(c.to_string()));
+        }
+    }
+
+    /// The leading text which every match of the string starts with.
+    pub fn leading_str(&self) -> &str {
+        match self.lits.first() {
+            Some(Lit::Str(m_str)) => m_str,
+            _ => "",
+        }
+    }
+
+    /// The bytes any non-empty match of the string can start with, delimiting
+    /// the value preceding it. If the string consists of groups separating that
+    /// value from another, it has to contain text which can't be left out.
+    fn delims(&self, before_value: bool) -> Result<Vec<u8>, Error> {
+        let mut delims = vec![];
+        for lit in &self.lits {
+            match lit {
+                Lit::Str(m_str) => {
+                    delims.push(m_str.as_bytes()[0]);
+                    return Ok(delims);
+                }
+                Lit::Opt(m_str) => delims.push(m_str.as_bytes()[0]),
+                Lit::Alt(alts) => {
+                    delims.extend(alts.iter().map(|alt| alt.as_bytes()[0]));
+                    delims.sort_unstable();
+                    delims.dedup();
+                    return Ok(delims);
+                }
+            }
+        }
+        ensure!(
+            !before_value || delims.is_empty(),
+            "ambiguous matching string: \"{}\" may match nothing, so it can't separate two values",
+            self.m_str
+        );
+        delims.sort_unstable();
+        delims.dedup();
+        Ok(delims)
+    }
+}
+
+/// How the text of a value is parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    /// Parsed as written, such as `{}` or `{:u32}`.
+    Normal,
+    /// Parsed as hexadecimal, written as `{:x}`.
+    Hex,
+    /// Parsed as binary, written as `{:b}`.
+    Binary,
+    /// Consisting of the bytes of a class, written as `{:[a-z]}` or `{:digit}`.
+    Class(Class),
+    /// Matched against a nested matching string, written as `{:"{},{}"}`.
+    Nested(String),
+}
+
+/// A value of a matching string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Value {
+    /// The value as written in the matching string, such as `{x:u16}`.
+    pub text: String,
+    /// What precedes the ':' or '=', such as `x` in `{x:u16}` or `_` in `{_}`.
+    pub name: String,
+    /// How the text of the value is parsed.
+    pub kind: Kind,
+    /// The type the value is parsed into as written, such as `u16` in `{x:u16}`,
+    /// or empty if none is given.
+    pub ty: String,
+    /// Whether the value ends at the last possible delimiter, written as `{:+}`.
+    pub greedy: bool,
+    /// The value used when the matched text is empty or fails to parse as written,
+    /// such as `8080` in `{port=8080}`.
+    pub default: Option<String>,
+}
+
+impl Value {
+    /// Whether the value is matched without being parsed, written as `{_}`.
+    pub fn is_skipped(&self) -> bool {
+        self.name == "_"
+    }
+}
+
+/// A parsed matching string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parsed {
+    /// The values, each with the string preceding it and the bytes delimiting it.
+    pub values: Vec<(Value, Segment, Vec<u8>)>,
+    /// The string trailing the last value.
+    pub tail: Segment,
+}
+
+/// Parses a matching string, checking that it's unambiguous.
+pub fn parse(m_str: &str) -> Result<Parsed, Error> {
+    let mut iter = m_str.chars().peekable();
+    let mut values: Vec<(Value, Segment)> = vec![];
+    let mut seg = Segment::default();
+    while let Some(c) = iter.next() {
+        match c {
+            '{' => match iter.next() {
+                None => bail!("missing token after '{{'"),
+                Some('{') => seg.push('{'),
+                Some('[') => {
+                    let text = parse_group(&mut iter, ']')?;
+                    ensure!(!text.is_empty(), "empty optional group \"{{[]}}\"");
+                    seg.m_str.push_str(&format!("{{[{text}]}}"));
+                    seg.lits.push(Lit::Opt(text));
+                }
+                Some('(') => {
+                    let text = parse_group(&mut iter, ')')?;
+                    let alts: Vec<String> = text.split('|').map(String::from).collect();
+                    ensure!(
+                        alts.iter().all(|alt| !alt.is_empty()),
+                        "empty alternative in \"{{({text})}}\". Use an optional group such as \"{{[text]}}\" instead"
+                    );
+                    for (i, alt) in alts.iter().enumerate() {
+                        ensure!(
+                            !alts[..i].contains(alt),
+                            "alternative \"{alt}\" appears more than once in \"{{({text})}}\""
+                        );
+                    }
+                    seg.m_str.push_str(&format!("{{({text})}}"));
+                    seg.lits.push(Lit::Alt(alts));
+                }
+                Some(first) => {
+                    let value = parse_value(&mut iter, first)?;
+                    if let Some(Lit::Alt(alts)) = seg.lits.last() {
+                        for (i, alt) in alts.iter().enumerate() {
+                            if let Some(prefix) =
+                                alts[..i].iter().find(|prefix| alt.starts_with(*prefix))
+                            {
+                                bail!(
+                                    "ambiguous matching string: alternative \"{prefix}\" is a prefix of \"{alt}\", \
+                                    and is always used since a value follows. Try listing \"{alt}\" first"
+                                )
+                            }
+                        }
+                    }
+                    values.push((value, core::mem::take(&mut seg)));
+                }
+            },
+            '}' => {
+                ensure!(
+                    iter.next() == Some('}'),
+                    "special token '}}' not properly used. Did you mean to write \"}}}}\"?"
+                );
+                seg.push('}');
+            }
+            c => seg.push(c),
+        }
+    }
+    let mut delims = Vec::with_capacity(values.len());
+    for (i, (value, _)) in values.iter().enumerate() {
+        // A value with a character class ends on its own, and a greedy value takes
+        // as much as it can, so the string following either may match nothing.
+        let bounded = matches!(value.kind, Kind::Class(_)) || value.greedy;
+        delims.push(match values.get(i + 1) {
+            Some((_, next_seg)) => next_seg.delims(!bounded)?,
+            None => seg.delims(false)?,
+        });
+    }
+    let values = values.into_iter().zip(delims);
+    Ok(Parsed {
+        values: values
+            .map(|((value, seg), delims)| (value, seg, delims))
+            .collect(),
+        tail: seg,
+    })
+}
+
+/// Reads the text of a group up until `close`, followed by the closing '}'.
+fn parse_group(iter: &mut Peekable<Chars>, close: char) -> Result<String, Error> {
+    let mut text = String::new();
+    loop {
+        match iter.next() {
+            Some(c) if c == close => break,
+            Some(c @ ('{' | '}' | '[' | '(')) => {
+                bail!(
+                    "unexpected '{c}' inside a group. Groups can't contain values or other groups"
+                )
+            }
+            Some(c) => text.push(c),
+            None => bail!("missing closing '{close}' in group"),
+        }
+    }
+    ensure!(
+        iter.next() == Some('}'),
+        "missing closing '}}' after '{close}' in group"
+    );
+    Ok(text)
+}
+
+/// Reads a character class such as `[^a-z_]` after its opening '[', up to and
+/// including the closing ']'.
+fn parse_class(iter: &mut Peekable<Chars>) -> Result<Class, Error> {
+    let negated = iter.next_if_eq(&'^').is_some();
+    let mut chars = vec![];
+    loop {
+        match iter.next() {
+            Some(']') => break,
+            Some('\\') => match iter.next() {
+                Some(c) => chars.push((c, true)),
+                None => bail!("missing character after '\\' in character class"),
+            },
+            Some(c) => chars.push((c, false)),
+            None => bail!("missing closing ']' in character class"),
+        }
+    }
+    ensure!(!chars.is_empty(), "empty character class \"[]\"");
+    if let Some((c, _)) = chars.iter().find(|(c, _)| !c.is_ascii()) {
+        bail!("character classes may only contain ASCII characters, found '{c}'");
+    }
+    let mut ranges = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let lo = chars[i].0 as u8;
+        if i + 2 < chars.len() && chars[i + 1] == ('-', false) {
+            let hi = chars[i + 2].0 as u8;
+            ensure!(
+                lo <= hi,
+                "invalid range '{}-{}' in character class",
+                lo as char,
+                hi as char
+            );
+            ranges.push((lo, hi));
+            i += 3;
+        } else {
+            ranges.push((lo, lo));
+            i += 1;
+        }
+    }
+    Ok(Class { negated, ranges })
+}
+
+/// Reads a nested matching string after its opening '"', up to and including the
+/// closing '"'. Quotes and backslashes inside of it are escaped with a backslash.
+fn parse_quoted(iter: &mut Peekable<Chars>) -> Result<String, Error> {
+    let mut string = String::new();
+    loop {
+        match iter.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match iter.next() {
+                Some(c) => string.push(c),
+                None => bail!("missing character after '\\' in nested matching string"),
+            },
+            Some(c) => string.push(c),
+            None => bail!("missing closing '\"' in nested matching string"),
+        }
+    }
+}
+
+/// Reads the default of a value after its '=', up to and including the closing '}'.
+fn parse_default(iter: &mut Peekable<Chars>) -> Result<String, Error> {
+    let mut text = String::new();
+    let mut depth = 0;
+    let mut in_str = false;
+    loop {
+        let Some(c) = iter.next() else {
+            bail!("missing closing '}}' after default value")
+        };
+        match c {
+            '}' if depth == 0 && !in_str => break,
+            '{' if !in_str => depth += 1,
+            '}' if !in_str => depth -= 1,
+            '"' => in_str = !in_str,
+            '\\' if in_str => {
+                text.push(c);
+                if let Some(c) = iter.next() {
+                    text.push(c);
+                }
+                continue;
+            }
+            _ => (),
+        }
+        text.push(c);
+    }
+    ensure!(!text.trim().is_empty(), "missing default value after '='");
+    Ok(text.trim().to_string())
+}
+
+/// Reads a value after its opening '{', up to and including the closing '}', given
+/// its first character.
+fn parse_value(iter: &mut Peekable<Chars>, first: char) -> Result<Value, Error> {
+    let mut name = String::new();
+    let mut spec = String::new();
+    let mut class = None;
+    let mut nested = None;
+    let mut default = None;
+    let mut pushing_name = true;
+    let rest = iter.clone();
+    let mut c = first;
+    loop {
+        match c {
+            '}' => break,
+            '=' => {
+                default = Some(parse_default(iter)?);
+                break;
+            }
+            ':' if pushing_name => {
+                pushing_name = false;
+                if iter.next_if_eq(&'[').is_some() {
+                    class = Some(parse_class(iter)?);
+                }
+            }
+            '"' if !pushing_name => {
+                nested = Some(parse_quoted(iter)?);
+                while iter.next_if(|c| c.is_whitespace()).is_some() {}
+                ensure!(
+                    matches!(iter.peek(), Some('}' | '=')),
+                    "missing closing '}}' after nested matching string"
+                );
+            }
+            c if pushing_name => name.push(c),
+            c => spec.push(c),
+        }
+        c = match iter.next() {
+            Some(c) => c,
+            None => bail!("missing closing '}}' after '{{'"),
+        };
+    }
+    let consumed = rest.clone().count() - iter.clone().count();
+    let text = format!("{{{first}{}", rest.take(consumed).collect::<String>());
+    let mut value = Value {
+        text,
+        name,
+        kind: Kind::Normal,
+        ty: String::new(),
+        greedy: false,
+        default,
+    };
+    if let Some(class) = class {
+        value.kind = Kind::Class(class);
+        value.ty = spec.trim().to_string();
+        return Ok(value);
+    }
+    let spec = spec.trim();
+    let spec = match spec.strip_prefix('+') {
+        Some(spec) => {
+            value.greedy = true;
+            spec.trim_start()
+        }
+        None => spec,
+    };
+    if let Some(nested) = nested {
+        value.kind = Kind::Nested(nested);
+        value.ty = spec.to_string();
+        return Ok(value);
+    }
+    let (kind, rest) = spec.split_once(char::is_whitespace).unwrap_or((spec, ""));
+    value.kind = match kind {
+        "" => Kind::Normal,
+        "x" => Kind::Hex,
+        "b" => Kind::Binary,
+        name => match Class::named(name) {
+            Some(class) => {
+                ensure!(
+                    !value.greedy,
+                    "a value with a character class always ends at the first character \
+                    outside of it, so it can't be greedy"
+                );
+                Kind::Class(class)
+            }
+            None => {
+                value.ty = spec.to_string();
+                return Ok(value);
+            }
+        },
+    };
+    value.ty = rest.trim().to_string();
+    Ok(value)
+}
+
+/// A segment preceding a value, alongside how the value is matched.
+pub struct Part<'a> {
+    /// The string preceding the value.
+    pub segment: &'a Segment,
+    /// The bytes delimiting the value.
+    pub delims: &'a [u8],
+    /// Whether the value is matched without being parsed.
+    pub skip: bool,
+    /// Whether the value ends at the last possible delimiter.
+    pub greedy: bool,
+    /// The bytes the value consists of, if bounded by a class.
+    pub class: Option<&'a Class>,
+}
+
+impl<'a> Part<'a> {
+    /// The part of a value of a parsed matching string.
+    pub fn new(value: &'a Value, segment: &'a Segment, delims: &'a [u8]) -> Self {
+        Self {
+            segment,
+            delims,
+            skip: value.is_skipped(),
+            greedy: value.greedy,
+            class: match &value.kind {
+                Kind::Class(class) => Some(class),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Calls `f` with the segments `parts` and `tail` are matched through, such as by
+/// `crate::match_values`.
+pub fn with_segments<R>(
+    parts: &[Part],
+    tail: &Segment,
+    f: impl FnOnce(&[crate::Segment], &crate::Segment) -> R,
+) -> R {
+    let segments = || parts.iter().map(|part| part.segment).chain([tail]);
+    let alts: Vec<Vec<&str>> = segments()
+        .flat_map(|segment| &segment.lits)
+        .filter_map(|lit| match lit {
+            Lit::Alt(alts) => Some(alts.iter().map(String::as_str).collect()),
+            _ => None,
+        })
+        .collect();
+    let mut alts = alts.iter();
+    let lits: Vec<Vec<crate::Lit>> = segments()
+        .map(|segment| {
+            let lits = segment.lits.iter().map(|lit| match lit {
+                Lit::Str(m_str) => crate::Lit::Str(m_str),
+                Lit::Opt(m_str) => crate::Lit::Opt(m_str),
+                Lit::Alt(_) => crate::Lit::Alt(alts.next().expect("every group is collected")),
+            });
+            lits.collect()
+        })
+        .collect();
+    let borrowed: Vec<crate::Segment> = parts
+        .iter()
+        .zip(&lits)
+        .map(|(part, lits)| crate::Segment {
+            m_str: &part.segment.m_str,
+            lits,
+            delims: part.delims,
+            skip: part.skip,
+            greedy: part.greedy,
+            class: part.class.map(Class::borrowed),
+        })
+        .collect();
+    let tail = crate::Segment {
+        m_str: &tail.m_str,
+        lits: &lits[parts.len()],
+        delims: &[],
+        skip: false,
+        greedy: false,
+        class: None,
+    };
+    f(&borrowed, &tail)
+}
@@ -1,38 +1,46 @@
 #![no_std]
-#![feature(alloc_error_handler)]
+#![no_main]
 
-extern crate alloc;
+use heapless::{String, Vec};
+use strp::{
+    stream::{Status, StreamParser},
+    try_parse, try_scan, TryParseError,
+};
 
 #[no_mangle]
-fn main() -> ! {
-    let _parse: u32 = strp::parse!("hello" => "{}");
-
-    let _scan: (u32, u32) = strp::scan!("hello" => "{}, {}");
+extern "C" fn _start() -> ! {
+    let _parse: u32 = strp::parse!("10" => "{}");
+    let _scan: (u32, i8) = strp::scan!("1, -2" => "{}, {}");
+
+    // Without the `alloc` feature, mismatches hold where the source stopped matching.
+    let source = "x = 5";
+    let mismatch: Result<u32, _> = try_parse!(source => "y = {}");
+    assert_eq!(mismatch, Err(TryParseError::ExpectedMismatch("y = ", 0)));
+    let source = "move 1.5 ff";
+    let _values: Result<(f32, u8), _> = try_scan!(source => "move {} {:x}");
+
+    let mut parser: StreamParser<(u16, u16), 32> = strp::stream_parser!("pos {} {}\n");
+    assert_eq!(parser.feed(b"pos 1"), Status::Incomplete);
+    assert_eq!(parser.feed(b"0 20\n"), Status::Done((10, 20), 5));
+
+    // Text and bytes are captured into fixed-capacity strings and vectors.
+    let (name, id): (String<16>, u32) = strp::scan!("user ferris#42" => "user {}#{}");
+    assert_eq!((name.as_str(), id), ("ferris", 42));
+    let payload: Vec<u8, 8> = strp::parse!(b"data:\x01\x02" => "data:{}");
+    assert_eq!(payload, [1, 2]);
+    let source = "user ferris";
+    let name: Result<String<4>, _> = try_parse!(source => "user {}");
+    assert_eq!(name, Err(TryParseError::CapacityExceeded));
+
+    let mut parser: StreamParser<(String<8>, u8), 32> = strp::stream_parser!("{}={}\n");
+    match parser.feed(b"led=1\n") {
+        Status::Done((key, value), _) => assert_eq!((key.as_str(), value), ("led", 1)),
+        _ => panic!(),
+    }
     loop {}
 }
 
 #[panic_handler]
-#[no_mangle]
-unsafe fn panic_handler(_info: &core::panic::PanicInfo) -> ! {
-    todo!()
-}
-
-#[alloc_error_handler]
-fn alloc_error(layout: core::alloc::Layout) -> ! {
-    panic!("allocation error: layout {layout:?}");
-}
-
-struct Allocator {}
-
-unsafe impl core::alloc::GlobalAlloc for Allocator {
-    unsafe fn alloc(&self, _layout: core::alloc::Layout) -> *mut u8 {
-        todo!()
-    }
-
-    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: core::alloc::Layout) {
-        todo!()
-    }
+fn panic_handler(_info: &core::panic::PanicInfo) -> ! {
+    loop {}
 }
-
-#[global_allocator]
-static TEST: Allocator = Allocator {};
@@ -13,4 +13,7 @@ fn main() {
 
     let (first, second): (String, String) = strp::scan!("first: {} second: {}");
     println!("first: {first}, second: {second}");
+
+    let port: std::io::Result<Option<u16>> = strp::prompt!("port: " => "{}", 3);
+    println!("{port:?}");
 }
@@ -5,195 +5,847 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use quote::quote;
-use std::{
-    iter::Peekable,
-    str::{Chars, FromStr},
-};
-use syn::{bracketed, parse::Parse, Expr, LitInt, LitStr, Token};
+use std::str::FromStr;
+use strp_core::syntax::{self, Class, Kind, Lit, Part, Segment, Value};
+use syn::{bracketed, parse::Parse, Expr, LitInt, LitStr, Token, Type};
+
+/// A matching string, written as a string literal, or as a `concat!`, `stringify!`,
+/// `include_str!` or `env!` of literals, which are expanded by the macros themselves.
+struct MStr {
+    value: String,
+    /// The matching string as written, so it can be forwarded to other macros.
+    tokens: proc_macro2::TokenStream,
+    /// The `include_str!` and `env!` invocations the string was expanded from, which
+    /// are invoked again in the generated code so changes to what they read are
+    /// picked up.
+    tracked: Vec<proc_macro2::TokenStream>,
+}
+
+impl Parse for MStr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let expr: Expr = input.parse()?;
+        MStr::from_expr(&expr).ok_or_else(|| {
+            syn::Error::new_spanned(
+                &expr,
+                "expected a string literal, or a `concat!`, `stringify!`, `include_str!` or `env!` of literals",
+            )
+        })
+    }
+}
+
+impl MStr {
+    /// Expands the matching string `expr`, returning `None` if it isn't one.
+    fn from_expr(expr: &Expr) -> Option<Self> {
+        let mut mstr = Self {
+            value: String::new(),
+            tokens: quote!(#expr),
+            tracked: vec![],
+        };
+        mstr.expand(expr)?;
+        Some(mstr)
+    }
+
+    /// Appends the string `expr` expands to onto the matching string.
+    fn expand(&mut self, expr: &Expr) -> Option<()> {
+        match expr {
+            Expr::Lit(lit) => match &lit.lit {
+                syn::Lit::Str(lit) => self.value.push_str(&lit.value()),
+                syn::Lit::Char(lit) => self.value.push(lit.value()),
+                syn::Lit::Int(lit) => self.value.push_str(lit.base10_digits()),
+                syn::Lit::Float(lit) => self.value.push_str(lit.base10_digits()),
+                syn::Lit::Bool(lit) => self.value.push_str(&lit.value.to_string()),
+                _ => return None,
+            },
+            Expr::Unary(syn::ExprUnary {
+                op: syn::UnOp::Neg(_),
+                expr,
+                ..
+            }) if matches!(**expr, Expr::Lit(_)) => {
+                self.value.push('-');
+                self.expand(expr)?;
+            }
+            Expr::Group(group) => self.expand(&group.expr)?,
+            Expr::Macro(mac) => {
+                let name = mac.mac.path.segments.last()?.ident.to_string();
+                let tokens = mac.mac.tokens.clone();
+                match name.as_str() {
+                    "concat" => {
+                        let parser =
+                            syn::punctuated::Punctuated::<Expr, Token![,]>::parse_terminated;
+                        for expr in syn::parse::Parser::parse2(parser, tokens).ok()? {
+                            self.expand(&expr)?;
+                        }
+                    }
+                    "stringify" => self.value.push_str(&tokens.to_string()),
+                    "include_str" => {
+                        let path: LitStr = syn::parse2(tokens).ok()?;
+                        let file = path.span().unwrap().local_file().unwrap_or_else(|| {
+                            panic!("can't find the file `include_str!` is used in")
+                        });
+                        let file = file.parent().unwrap_or(&file).join(path.value());
+                        let text = std::fs::read_to_string(&file)
+                            .unwrap_or_else(|e| panic!("couldn't read {}: {e}", file.display()));
+                        self.value.push_str(&text);
+                        self.tracked.push(quote!(::core::include_str!(#path)));
+                    }
+                    "env" => {
+                        let parser =
+                            syn::punctuated::Punctuated::<LitStr, Token![,]>::parse_terminated;
+                        let args = syn::parse::Parser::parse2(parser, tokens).ok()?;
+                        let name = args.first()?;
+                        let value = std::env::var(name.value()).unwrap_or_else(|_| {
+                            match args.iter().nth(1) {
+                                Some(message) => panic!("{}", message.value()),
+                                None => panic!(
+                                    "environment variable `{}` not defined at compile time",
+                                    name.value()
+                                ),
+                            }
+                        });
+                        self.value.push_str(&value);
+                        self.tracked.push(quote!(::core::env!(#name)));
+                    }
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Generates the matching string as a string literal, preceded by the items
+    /// tracking the files and environment variables it was expanded from.
+    fn quote(&self) -> (proc_macro2::TokenStream, LitStr) {
+        let tracked = &self.tracked;
+        (
+            quote!(#(const _: &str = #tracked;)*),
+            LitStr::new(&self.value, Span::call_site()),
+        )
+    }
+}
 
 struct Sparse {
     input: Expr,
     #[allow(unused)]
     arrow: Token![=>],
     mstr: String,
+    args: Args,
 }
 
 impl Parse for Sparse {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let expr = input.parse()?;
         let arrow = input.parse()?;
-        let string: syn::LitStr = input.parse()?;
+        let string: MStr = input.parse()?;
+        let args = input.parse()?;
         Ok(Self {
             input: expr,
             arrow,
-            mstr: string.value(),
+            mstr: string.value,
+            args,
+        })
+    }
+}
+
+impl Sparse {
+    /// Sensetizes and flattens the matching string, assigning inlined values to
+    /// the arguments given after it.
+    #[allow(clippy::type_complexity)]
+    fn flatten(&self) -> (Vec<(Var, Segment, Vec<u8>)>, Segment) {
+        let (mut vars, tail) = flatten(&self.mstr);
+        self.args
+            .bind(&mut vars, |expr| syn::parse_quote!(*(#expr)));
+        (vars, tail)
+    }
+}
+
+/// An argument given after the matching string, which values are assigned through.
+enum Arg {
+    /// An argument written as `name = &mut place`, used by `{name}`.
+    Named(Ident, Expr),
+    /// An argument written as `&mut place`, used by the next `{}`.
+    Positional(Expr),
+}
+
+impl Parse for Arg {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(if input.peek(syn::Ident) && input.peek2(Token![=]) {
+            let name = input.parse()?;
+            input.parse::<Token![=]>()?;
+            Self::Named(name, input.parse()?)
+        } else {
+            Self::Positional(input.parse()?)
+        })
+    }
+}
+
+/// The arguments given after the matching string, following a ','.
+#[derive(Default)]
+struct Args {
+    args: Vec<Arg>,
+    /// The arguments as written, forwarded to other macros.
+    tokens: proc_macro2::TokenStream,
+}
+
+impl Parse for Args {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.parse::<Option<Token![,]>>()?.is_none() {
+            return Ok(Self::default());
+        }
+        let tokens: proc_macro2::TokenStream = input.parse()?;
+        let args = syn::parse::Parser::parse2(
+            syn::punctuated::Punctuated::<Arg, Token![,]>::parse_terminated,
+            tokens.clone(),
+        )?;
+        Ok(Self {
+            args: args.into_iter().collect(),
+            tokens,
         })
     }
 }
 
+impl Args {
+    /// Generates the arguments to pass on to another macro, including the leading ','.
+    fn forward(&self) -> proc_macro2::TokenStream {
+        let tokens = &self.tokens;
+        if tokens.is_empty() {
+            quote!()
+        } else {
+            quote!(, #tokens)
+        }
+    }
+
+    /// Removes the last argument, which is the default of `parse_or` and `scan_or`.
+    fn pop_default(&mut self, name: &str) -> Expr {
+        let default = match self.args.pop() {
+            Some(Arg::Positional(default)) => default,
+            Some(Arg::Named(..)) => panic!("the default of `{name}` can't be a named argument"),
+            None => panic!("missing default value after the matching string of `{name}`"),
+        };
+        let args = self.args.iter().map(|arg| match arg {
+            Arg::Named(name, expr) => quote!(#name = #expr),
+            Arg::Positional(expr) => quote!(#expr),
+        });
+        self.tokens = quote!(#(#args),*);
+        default
+    }
+
+    /// Makes values with a named argument, and values without a name if there are
+    /// positional arguments, refer to the place `place` gives for the argument.
+    fn bind(&self, vars: &mut [(Var, Segment, Vec<u8>)], place: impl Fn(&Expr) -> Expr) {
+        let mut positional = vec![];
+        let mut named = vec![];
+        for arg in &self.args {
+            match arg {
+                Arg::Positional(expr) => {
+                    assert!(
+                        named.is_empty(),
+                        "positional arguments have to come before named arguments"
+                    );
+                    positional.push(expr);
+                }
+                Arg::Named(name, expr) => {
+                    assert!(
+                        named.iter().all(|(other, _, _)| *other != name),
+                        "duplicate argument named `{name}`"
+                    );
+                    named.push((name, expr, false));
+                }
+            }
+        }
+        let unnamed = vars
+            .iter()
+            .filter(|var| !var.0.skip && var.0.inlined.is_none())
+            .count();
+        assert!(
+            positional.is_empty() || positional.len() == unnamed,
+            "{} positional arguments were given for {unnamed} values written as \"{{}}\"",
+            positional.len(),
+        );
+        let mut positional = positional.into_iter();
+        for (var, _, _) in vars.iter_mut().filter(|var| !var.0.skip) {
+            match &var.inlined {
+                Some(Expr::Path(path)) => {
+                    if let Some((_, expr, used)) = named
+                        .iter_mut()
+                        .find(|(name, _, _)| path.path.is_ident(*name))
+                    {
+                        *used = true;
+                        var.inlined = Some(place(expr));
+                    }
+                }
+                Some(_) => (),
+                None => var.inlined = positional.next().map(&place),
+            }
+        }
+        if let Some((name, _, _)) = named.iter().find(|(_, _, used)| !used) {
+            panic!("named argument `{name}` is never used")
+        }
+    }
+}
+
 enum VarTy {
     Normal,
     Hex,
     Binary,
+    Class(Class),
+    /// A matching string nested in the value, written as `{:"{},{}"}`, flattened
+    /// into its values and trailing string.
+    Nested(Vec<(Var, Segment, Vec<u8>)>, Segment),
+}
+
+impl VarTy {
+    fn class(&self) -> Option<&Class> {
+        match self {
+            VarTy::Class(class) => Some(class),
+            _ => None,
+        }
+    }
+}
+
+/// Generates the `strp::__private::Class` for a class.
+fn quote_class(class: &Class) -> proc_macro2::TokenStream {
+    let negated = class.negated;
+    let ranges = class.ranges.iter().map(|(lo, hi)| quote!((#lo, #hi)));
+    quote! {
+        ::strp::__private::Class {
+            negated: #negated,
+            ranges: &[#(#ranges),*],
+        }
+    }
 }
 
 struct Var {
-    #[allow(unused)]
-    inlined: Option<Ident>,
+    /// The place the value is assigned to, written as `{x}` or `{cfg.w}`.
+    inlined: Option<Expr>,
     ty: VarTy,
+    /// The type the value is parsed into, written after the ':' as in `{:u16}`.
+    rust_ty: Option<Type>,
+    /// Whether the value ends at the last possible delimiter, written as `{:+}`.
+    greedy: bool,
+    /// Whether the value is matched without being parsed, written as `{_}`.
+    skip: bool,
+    /// The value used when the matched text is empty or fails to parse, written
+    /// as `{port=8080}`.
+    default: Option<Expr>,
+    /// The value as written in the matching string, such as `{x:u16}`.
+    text: String,
+}
+
+/// Parses the place an inlined value is assigned to, such as `x`, `cfg.w`, `arr[2]`
+/// or `*p`.
+fn sensetize_place(place: &str) -> Expr {
+    fn is_place(expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(_) => true,
+            Expr::Field(field) => is_place(&field.base),
+            Expr::Index(index) => is_place(&index.expr),
+            Expr::Paren(paren) => is_place(&paren.expr),
+            Expr::Unary(unary) => matches!(unary.op, syn::UnOp::Deref(_)),
+            _ => false,
+        }
+    }
+    match syn::parse_str(place) {
+        Ok(expr) if is_place(&expr) => expr,
+        _ => panic!(
+            "\"{{{place}}}\" can't be assigned to. Inlined values have to be written as a \
+            variable such as {{x}}, a field such as {{cfg.w}}, an index such as {{arr[2]}}, \
+            or a dereference such as {{*p}}"
+        ),
+    }
 }
 
-struct Sensetized {
-    m_str: String,
-    content: Option<(Var, Option<char>, Option<Box<Sensetized>>)>,
+/// Generates the `strp::__private::Segment` for a string, given the value following
+/// it and its delimiters.
+fn quote_segment(segment: &Segment, delims: &[u8], var: Option<&Var>) -> proc_macro2::TokenStream {
+    let m_str = &segment.m_str;
+    let lits = segment.lits.iter().map(|lit| match lit {
+        Lit::Str(m_str) => quote!(::strp::__private::Lit::Str(#m_str)),
+        Lit::Opt(m_str) => quote!(::strp::__private::Lit::Opt(#m_str)),
+        Lit::Alt(alts) => quote!(::strp::__private::Lit::Alt(&[#(#alts),*])),
+    });
+    let delims = proc_macro2::Literal::byte_string(delims);
+    let greedy = var.is_some_and(|var| var.greedy);
+    let skip = var.is_some_and(|var| var.skip);
+    let class = match var.and_then(|var| var.ty.class()) {
+        Some(class) => {
+            let class = quote_class(class);
+            quote!(::core::option::Option::Some(#class))
+        }
+        None => quote!(::core::option::Option::None),
+    };
+    quote! {
+        ::strp::__private::Segment {
+            m_str: #m_str,
+            lits: &[#(#lits),*],
+            delims: #delims,
+            skip: #skip,
+            greedy: #greedy,
+            class: #class,
+        }
+    }
 }
 
-fn sensetize_single(iter: &mut Peekable<Chars>) -> Sensetized {
-    let mut m_str = String::new();
-    let mut var_option = None;
-    'outer: while let Some(c) = iter.next() {
-        if c == '{' {
-            let mut c = iter.next().expect("missing token after '{'");
-            if c == '{' {
-                m_str.push('{')
-            } else {
-                let mut ident = String::new();
-                let mut ty_str = String::new();
-                let mut pushing_ident = true;
-                loop {
-                    match c {
-                        '}' => {
-                            let inlined = if ident.is_empty() {
-                                None
-                            } else {
-                                Some(Ident::new(&ident, Span::call_site()))
-                            };
-                            let ty = if !ty_str.is_empty() {
-                                let determining_character = ty_str.pop().unwrap();
-                                match determining_character {
-                                    'x' => VarTy::Hex,
-                                    'b' => VarTy::Binary,
-                                    _ => panic!("invalid parsing type after ':'. Try writing {{{ident}:b}} or {{{ident}:x}}")
-                                }
-                            } else {
-                                VarTy::Normal
-                            };
-                            var_option = Some(Var { inlined, ty });
-                            break 'outer;
+impl Var {
+    /// Interprets a value of a parsed matching string, parsing its place, type and
+    /// default.
+    fn new(value: Value) -> Self {
+        let ident = &value.name;
+        let rust_ty = (!value.ty.is_empty()).then(|| {
+            syn::parse_str::<Type>(&value.ty).unwrap_or_else(|_| {
+                panic!(
+                    "invalid parsing type after ':'. Try writing {{{ident}:b}}, {{{ident}:x}}, \
+                    a character class such as {{{ident}:digit}} or {{{ident}:[a-z]}}, \
+                    or a type such as {{{ident}:u32}}"
+                )
+            })
+        });
+        let skip = value.is_skipped();
+        let (ty, rust_ty) = match value.kind {
+            Kind::Normal => (VarTy::Normal, rust_ty),
+            Kind::Hex => (VarTy::Hex, rust_ty),
+            Kind::Binary => (VarTy::Binary, rust_ty),
+            Kind::Class(class) => (VarTy::Class(class), rust_ty),
+            Kind::Nested(nested) => {
+                let (mut vars, tail) = flatten(&nested);
+                assert!(
+                    vars.iter().all(|var| var.0.inlined.is_none()),
+                    "values in a nested matching string can't be inlined"
+                );
+                let count = captures(&vars).count();
+                assert!(
+                    count >= 2,
+                    "nested matching strings need at least two values, since they're parsed into a tuple"
+                );
+                match rust_ty {
+                    Some(Type::Tuple(tuple)) if tuple.elems.len() == count => {
+                        let vars = vars.iter_mut().map(|var| &mut var.0).filter(|var| !var.skip);
+                        for (var, ty) in vars.zip(tuple.elems) {
+                            var.rust_ty.get_or_insert(ty);
                         }
-                        ':' => pushing_ident = false,
-                        c if pushing_ident => ident.push(c),
-                        c => ty_str.push(c),
                     }
-                    c = iter.next().expect("missibng closing '}' after '{'")
+                    Some(_) => panic!(
+                        "the type of a nested matching string has to be a tuple with a type for each of its values"
+                    ),
+                    None => (),
                 }
+                (VarTy::Nested(vars, tail), None)
             }
-        } else if c == '}' {
-            assert_eq!(
-                iter.next(),
-                Some('}'),
-                "special token '}}' not properly used. Did you mean to write \"}}}}\"?"
-            );
-            m_str.push('}');
+        };
+        assert!(
+            !skip || rust_ty.is_none(),
+            "skipped values aren't parsed, so they can't be given a type"
+        );
+        assert!(
+            !skip || value.default.is_none(),
+            "skipped values aren't parsed, so they can't be given a default"
+        );
+        let inlined = if ident.is_empty() || skip {
+            None
         } else {
-            m_str.push(c)
+            Some(sensetize_place(ident))
+        };
+        let default = value.default.map(|text| {
+            syn::parse_str(&text).unwrap_or_else(|_| panic!("invalid default value `{text}`"))
+        });
+        Self {
+            inlined,
+            ty,
+            rust_ty,
+            greedy: value.greedy,
+            skip,
+            default,
+            text: value.text,
         }
     }
-    if let Some(var_option) = var_option {
-        let delim_option = iter.peek().copied();
-        Sensetized {
-            m_str,
-            content: Some((var_option, delim_option, None)),
-        }
+}
+
+/// Parses a matching string into its matched values, each paired with the string
+/// preceding it and its delimiters, followed by the trailing string.
+#[allow(clippy::type_complexity)]
+fn flatten(m_str: &str) -> (Vec<(Var, Segment, Vec<u8>)>, Segment) {
+    let parsed = syntax::parse(m_str).unwrap_or_else(|err| panic!("{err}"));
+    let vars = parsed.values.into_iter();
+    let vars = vars.map(|(value, segment, delims)| (Var::new(value), segment, delims));
+    (vars.collect(), parsed.tail)
+}
+
+/// The most values parsed as a single tuple. More values are parsed in chunks of
+/// this size, chained through `strp::__private::Chain`.
+const TUPLE_LEN: usize = 16;
+
+/// Generates the type values of the types `tys` are parsed as together.
+fn multiple_ty(tys: &[proc_macro2::TokenStream]) -> proc_macro2::TokenStream {
+    if tys.len() <= TUPLE_LEN {
+        quote!((#(#tys,)*))
     } else {
-        Sensetized {
-            m_str,
-            content: None,
+        let (first, rest) = tys.split_at(TUPLE_LEN);
+        let rest = multiple_ty(rest);
+        quote!(::strp::__private::Chain<(#(#first,)*), #rest>)
+    }
+}
+
+/// Generates the field access of the `i`th of `len` values parsed as `multiple_ty`.
+fn multiple_index(i: usize, len: usize) -> proc_macro2::TokenStream {
+    let index = |i: usize| LitInt::new(&i.to_string(), Span::call_site());
+    if len <= TUPLE_LEN {
+        let i = index(i);
+        quote!(.#i)
+    } else if i < TUPLE_LEN {
+        let (chunk, i) = (index(0), index(i));
+        quote!(.#chunk.#i)
+    } else {
+        let (chunk, rest) = (index(1), multiple_index(i - TUPLE_LEN, len - TUPLE_LEN));
+        quote!(.#chunk #rest)
+    }
+}
+
+/// Generates the type a value is parsed as, and the expression turning `raw`, the
+/// parsed value, into the matched value. Items required by nested matching strings
+/// are added to `items`.
+fn quote_value(
+    var: &Var,
+    raw: proc_macro2::TokenStream,
+    items: &mut Vec<proc_macro2::TokenStream>,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    if let Some(default) = &var.default {
+        let value_ident = Ident::new("value", Span::mixed_site());
+        let (ty, value) = quote_parsed(var, quote!(#value_ident), items);
+        return (
+            quote!(::strp::__private::Or<#ty>),
+            quote!(match #raw.into_inner() {
+                Some(#value_ident) => #value,
+                None => #default,
+            }),
+        );
+    }
+    quote_parsed(var, raw, items)
+}
+
+/// Same as `quote_value`, ignoring the default of the value.
+fn quote_parsed(
+    var: &Var,
+    raw: proc_macro2::TokenStream,
+    items: &mut Vec<proc_macro2::TokenStream>,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let rust_ty = match &var.rust_ty {
+        Some(ty) => quote!(#ty),
+        None => quote!(_),
+    };
+    match &var.ty {
+        VarTy::Normal | VarTy::Class(_) => (rust_ty, raw),
+        VarTy::Hex => (
+            quote!(::strp::__private::Hex<#rust_ty>),
+            quote!(#raw.into_inner()),
+        ),
+        VarTy::Binary => (
+            quote!(::strp::__private::Binary<#rust_ty>),
+            quote!(#raw.into_inner()),
+        ),
+        VarTy::Nested(vars, tail) => {
+            let pattern = Ident::new(&format!("__Pattern{}", items.len()), Span::mixed_site());
+            let segments = vars
+                .iter()
+                .map(|var| quote_segment(&var.1, &var.2, Some(&var.0)));
+            let tail = quote_segment(tail, &[], None);
+            items.push(quote! {
+                struct #pattern;
+
+                impl ::strp::__private::Pattern for #pattern {
+                    const SEGMENTS: &'static [::strp::__private::Segment<'static>] = &[#(#segments),*];
+                    const TAIL: &'static ::strp::__private::Segment<'static> = &#tail;
+                }
+            });
+            let nested_ident = Ident::new("nested", Span::mixed_site());
+            let count = captures(vars).count();
+            let (tys, values): (Vec<_>, Vec<_>) = captures(vars)
+                .enumerate()
+                .map(|(i, var)| {
+                    let index = multiple_index(i, count);
+                    quote_value(var, quote!(#nested_ident #index), items)
+                })
+                .unzip();
+            let tys = multiple_ty(&tys);
+            (
+                quote!(::strp::__private::Nested<#pattern, #tys>),
+                quote!({
+                    let #nested_ident = #raw.into_inner();
+                    (#(#values),*)
+                }),
+            )
+        }
+    }
+}
+
+/// The values of a flattened string which are parsed, leaving out skipped ones.
+fn captures(vars: &[(Var, Segment, Vec<u8>)]) -> impl Iterator<Item = &Var> {
+    vars.iter().map(|var| &var.0).filter(|var| !var.skip)
+}
+
+/// Matches a source string known while expanding against the values and trailing
+/// string of a matching string through `strp_core::match_values`, as is done at
+/// runtime, returning the text of each value which isn't skipped. `accept` decides
+/// whether the text of a value parses into it.
+fn match_expanded<'a>(
+    source: &'a str,
+    vars: &[(Var, Segment, Vec<u8>)],
+    tail: &Segment,
+    accept: &dyn Fn(&Var, &str) -> bool,
+) -> Option<Vec<&'a str>> {
+    let parsed: Vec<&Var> = captures(vars).collect();
+    let parts: Vec<Part> = vars
+        .iter()
+        .map(|(var, segment, delims)| Part {
+            segment,
+            delims,
+            skip: var.skip,
+            greedy: var.greedy,
+            class: var.ty.class(),
+        })
+        .collect();
+    let mut texts = vec![];
+    let found =
+        syntax::with_segments(&parts, tail, |segments, tail| {
+            let found =
+                strp_core::match_values(source.as_bytes(), segments, tail, true, |i, span| {
+                    match source.get(span) {
+                        Some(text) if accept(parsed[i], text) => {
+                            texts.truncate(i);
+                            texts.push(text);
+                            Ok(())
+                        }
+                        _ => Err(()),
+                    }
+                });
+            found.is_ok()
+        });
+    found.then_some(texts)
+}
+
+/// The name of the type of `var` if it's written as a single identifier, such as
+/// `u32`.
+fn primitive_ty(var: &Var) -> Option<String> {
+    match &var.rust_ty {
+        Some(Type::Path(path)) if path.qself.is_none() => {
+            path.path.get_ident().map(Ident::to_string)
         }
+        _ => None,
     }
 }
 
-fn sensetize_multiple(iter: &mut Peekable<Chars>) -> Sensetized {
-    let mut cur = sensetize_single(iter);
-    if iter.peek().is_some() {
-        if let Some(content) = &mut cur.content {
-            content.2 = Some(Box::new(sensetize_multiple(iter)));
+/// Whether `text` parses into `var` while expanding, which is assumed for values
+/// whose type isn't one of the primitive types strp parses.
+fn parses_expanded(var: &Var, text: &str) -> bool {
+    if var.default.is_some() {
+        return true;
+    }
+    let radix = match &var.ty {
+        VarTy::Nested(vars, tail) => {
+            return match_expanded(text, vars, tail, &parses_expanded).is_some();
         }
+        VarTy::Hex => 16,
+        VarTy::Binary => 2,
+        VarTy::Normal | VarTy::Class(_) => 10,
+    };
+    let ty = primitive_ty(var);
+    macro_rules! parses {
+        ($($int:ident),*) => {
+            match ty.as_deref() {
+                $(Some(stringify!($int)) => $int::from_str_radix(text, radix).is_ok(),)*
+                Some("f32") if radix == 10 => text.parse::<f32>().is_ok(),
+                Some("f64") if radix == 10 => text.parse::<f64>().is_ok(),
+                _ => true,
+            }
+        };
     }
-    cur
+    parses!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128)
 }
 
 #[doc(hidden)]
 #[proc_macro]
-pub fn try_parse_proc(ts: TokenStream) -> TokenStream {
+pub fn unwrap_proc(ts: TokenStream) -> TokenStream {
     let sparse: Sparse = syn::parse(ts).expect("string parsing failed");
-    let sensetized = sensetize_multiple(&mut sparse.mstr.chars().peekable());
-    let source = sparse.input;
-    let m_str = sensetized.m_str;
-    let (var, delim, next) = if let Some(content) = sensetized.content {
-        (
-            content.0,
-            {
-                if let Some(c) = content.1 {
-                    quote!(::core::option::Option::Some(#c as u8))
-                } else {
-                    quote!(::core::option::Option::None)
-                }
-            },
-            content.2,
-        )
-    } else {
-        panic!("missing \"{{}}\"")
-    };
-    let var_ident = Ident::new("var", Span::mixed_site());
-    let result_ident = Ident::new("__parse_result", Span::mixed_site());
-    let (var_ty, var_get) = {
-        match var.ty {
-            VarTy::Normal => (quote!(_), quote!(#var_ident)),
-            VarTy::Hex => (
-                quote!(::strp::__private::Hex<_>),
-                quote!(#var_ident.into_inner()),
-            ),
-            VarTy::Binary => (
-                quote!(::strp::__private::Binary<_>),
-                quote!(#var_ident.into_inner()),
-            ),
-        }
-    };
-    let var_match = quote! {
-        match #var_ident{
-            Ok(#var_ident) => Ok(#var_get),
-            Err(e) => Err(e),
+    unwrap_impl(sparse).into()
+}
+
+/// Generates the expression behind `parse` and `scan`, which unwraps the result of
+/// `try_match_impl`, panicking with the input, the matching string and where it failed.
+fn unwrap_impl(sparse: Sparse) -> proc_macro2::TokenStream {
+    let (vars, tail) = sparse.flatten();
+    unwrap_vars(&sparse, vars, tail)
+}
+
+/// Generates the expression behind `unwrap_impl` for already flattened values.
+fn unwrap_vars(
+    sparse: &Sparse,
+    vars: Vec<(Var, Segment, Vec<u8>)>,
+    tail: Segment,
+) -> proc_macro2::TokenStream {
+    let values = vars.iter().map(|var| &var.0.text);
+    let pattern = &sparse.mstr;
+    let source = &sparse.input;
+    // A source string known while expanding which can't match always panics.
+    if let Some(mstr) = MStr::from_expr(source) {
+        assert!(
+            match_expanded(&mstr.value, &vars, &tail, &parses_expanded).is_some(),
+            "\"{}\" never matches \"{pattern}\", so this always panics",
+            mstr.value.escape_debug(),
+        );
+    }
+    let source_ident = Ident::new("source", Span::mixed_site());
+    let failed = Ident::new("failed", Span::mixed_site());
+    let result = try_match_impl(
+        &syn::parse_quote!(#source_ident),
+        &vars,
+        tail,
+        false,
+        Some(&failed),
+    );
+    quote! {
+        {
+            let #source_ident = &#source;
+            let #failed = ::core::cell::Cell::new(0usize);
+            match #result {
+                Ok(ok) => ok,
+                Err(e) => ::strp::__private::parse_failed(
+                    e,
+                    ::core::convert::AsRef::<[u8]>::as_ref(#source_ident),
+                    #pattern,
+                    &[#(#values),*],
+                    #failed.get(),
+                ),
+            }
         }
-    };
-    let ret = if let Some(next) = next {
+    }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn try_match_proc(ts: TokenStream) -> TokenStream {
+    let sparse: Sparse = syn::parse(ts).expect("string parsing failed");
+    let (vars, tail) = sparse.flatten();
+    try_match_impl(&sparse.input, &vars, tail, false, None).into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn try_match_prefix_proc(ts: TokenStream) -> TokenStream {
+    let sparse: Sparse = syn::parse(ts).expect("string parsing failed");
+    let (vars, tail) = sparse.flatten();
+    try_match_impl(&sparse.input, &vars, tail, true, None).into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn is_match_proc(ts: TokenStream) -> TokenStream {
+    let sparse: Sparse = syn::parse(ts).expect("string parsing failed");
+    let (mut vars, tail) = sparse.flatten();
+    for (var, _, _) in &mut vars {
         assert!(
-            next.content.is_none(),
-            "attempting to parse multiple values during a `parse!`. Try using `scan!` instead."
+            var.inlined.is_none(),
+            "`is_match` doesn't assign values, so they can't be inlined or given as arguments"
         );
-        let m_str = next.m_str;
-        quote! {
-            if iter.clone().eq(#m_str.bytes()){
-                #var_match
-            } else{
-                let err: ::strp::__private::alloc::string::String = iter.map(|b| b as char).collect();
-                Err(::strp::TryParseError::ExpectedMismatch(#m_str, err))
+        // Values are only parsed if they're given a type to check against, except
+        // that hexadecimal and binary values are checked to consist of digits.
+        if var.rust_ty.is_none() {
+            match var.ty {
+                VarTy::Hex => {
+                    var.ty = VarTy::Normal;
+                    var.rust_ty = Some(syn::parse_quote!(::strp::__private::Digits<16>));
+                }
+                VarTy::Binary => {
+                    var.ty = VarTy::Normal;
+                    var.rust_ty = Some(syn::parse_quote!(::strp::__private::Digits<2>));
+                }
+                VarTy::Nested(..) => (),
+                _ => var.skip = true,
             }
         }
+    }
+    let result = try_match_impl(&sparse.input, &vars, tail, false, None);
+    quote!(#result.is_ok()).into()
+}
+
+/// Returns the ident of the `Cell` the index of the segment a match fails in is
+/// stored in, alongside its declaration if it isn't declared by the caller.
+fn declare_failed(failed: Option<&Ident>) -> (Ident, proc_macro2::TokenStream) {
+    match failed {
+        Some(failed) => (failed.clone(), quote!()),
+        None => {
+            let failed = Ident::new("failed", Span::mixed_site());
+            let declare = quote!(let #failed = ::core::cell::Cell::new(0usize););
+            (failed, declare)
+        }
+    }
+}
+
+/// Generates the expression behind `try_parse` and `try_scan`, matching any number
+/// of values. A single value is returned as is, keeping the error of its type, and
+/// other numbers of values are returned as a tuple. If `prefix` is set the trailing
+/// string only has to match the start of what's left of the source, which is
+/// returned alongside the parsed values. If `failed` is given, the index of the
+/// segment a match fails in is stored in it.
+fn try_match_impl(
+    source: &Expr,
+    vars: &[(Var, Segment, Vec<u8>)],
+    tail: Segment,
+    prefix: bool,
+    failed: Option<&Ident>,
+) -> proc_macro2::TokenStream {
+    if captures(vars).count() == 1 {
+        try_single_impl(source, vars, tail, prefix, failed)
+    } else {
+        try_multiple_impl(source, vars, tail, prefix, failed)
+    }
+}
+
+/// Generates the expression matching a single value for `try_match_impl`.
+fn try_single_impl(
+    source: &Expr,
+    vars: &[(Var, Segment, Vec<u8>)],
+    tail: Segment,
+    prefix: bool,
+    failed: Option<&Ident>,
+) -> proc_macro2::TokenStream {
+    let segments = vars
+        .iter()
+        .map(|(var, segment, delims)| quote_segment(segment, delims, Some(var)));
+    let var = captures(vars).next().expect("a single value is matched");
+    let var_ident = Ident::new("var", Span::mixed_site());
+    let result_ident = Ident::new("__parse_result", Span::mixed_site());
+    let mut items = vec![];
+    let (var_ty, var_get) = quote_value(var, quote!(#var_ident), &mut items);
+    let tail = quote_segment(&tail, &[], None);
+    let (failed, declare_failed) = declare_failed(failed);
+    let end = !prefix;
+    let (pos, ok) = if prefix {
+        (
+            quote!(pos),
+            quote!((#var_get, ::strp::__private::Rest::rest(source, pos))),
+        )
     } else {
-        quote!(#var_match)
+        (quote!(_), quote!(#var_get))
     };
     let block_quote = quote! {
             (|| {
+                #(#items)*
                 let source = &#source;
                 let slice = ::core::convert::AsRef::<[u8]>::as_ref(source);
-                let mut iter = slice.iter().cloned().peekable();
-                let #var_ident: Result<#var_ty, _> = ::strp::__private::parse_single(&mut iter, #m_str, #delim);
-                #ret
+                #declare_failed
+                match ::strp::__private::match_single::<#var_ty>(
+                    slice,
+                    &[#(#segments),*],
+                    &#tail,
+                    #end,
+                    &#failed,
+                ) {
+                    Ok((#var_ident, #pos)) => Ok(#ok),
+                    Err(e) => Err(e),
+                }
             })()
     };
-    let assign_or_ret = if let Some(inlined) = var.inlined {
-        quote! {
+    let assign_or_ret = match (&var.inlined, prefix) {
+        (Some(inlined), false) => quote! {
             match #result_ident{
                 Ok(ok) => {
                     #inlined = ok;
@@ -201,171 +853,270 @@ pub fn try_parse_proc(ts: TokenStream) -> TokenStream {
                 }
                 Err(e) => Err(e),
             }
-        }
-    } else {
-        quote!(#result_ident)
+        },
+        (Some(inlined), true) => quote! {
+            match #result_ident{
+                Ok((ok, rest)) => {
+                    #inlined = ok;
+                    Ok(rest)
+                }
+                Err(e) => Err(e),
+            }
+        },
+        (None, _) => quote!(#result_ident),
     };
     quote! {
         {(||{
-            extern crate alloc;
             let #result_ident = { #block_quote };
             #assign_or_ret
         })()}
     }
-    .into()
 }
 
-#[doc(hidden)]
-#[proc_macro]
-pub fn try_scan_proc(ts: TokenStream) -> TokenStream {
-    let sparse: Sparse = syn::parse(ts).expect("string parsing failed");
-    let sensetized = sensetize_multiple(&mut sparse.mstr.chars().peekable());
-    let mut vars = vec![];
-    let mut tail = None;
-    let mut cur = Box::new(sensetized);
-    loop {
-        let m_str = cur.m_str;
-        if let Some(content) = cur.content {
-            vars.push((content.0, m_str, content.1));
-            if let Some(next) = content.2 {
-                cur = next;
-            } else {
-                break;
-            }
-        } else {
-            tail = Some(m_str);
-            break;
-        }
-    }
+/// Generates the expression matching any number of values other than one for
+/// `try_match_impl`, returning them as a tuple.
+fn try_multiple_impl(
+    source: &Expr,
+    vars: &[(Var, Segment, Vec<u8>)],
+    tail: Segment,
+    prefix: bool,
+    failed: Option<&Ident>,
+) -> proc_macro2::TokenStream {
     let result_ident = Ident::new("__v_parse_result", Span::mixed_site());
     let mut quote_slice = vec![];
     let mut ret_tuple = vec![];
     let mut ret_assign = vec![];
     let mut type_vec = vec![];
-    for (i, var) in vars.into_iter().enumerate() {
-        let index = LitInt::new(&i.to_string(), Span::call_site());
-        let (ty, get_val) = {
-            match var.0.ty {
-                VarTy::Normal => (quote!(_), quote!(#result_ident.#index)),
-                VarTy::Hex => (
-                    quote!(::strp::__private::Hex<_>),
-                    quote!(#result_ident.#index.into_inner()),
-                ),
-                VarTy::Binary => (
-                    quote!(::strp::__private::Binary<_>),
-                    quote!(#result_ident.#index.into_inner()),
-                ),
-            }
-        };
+    let mut items = vec![];
+    let count = vars.iter().filter(|var| !var.0.skip).count();
+    for var in vars {
+        quote_slice.push(quote_segment(&var.1, &var.2, Some(&var.0)));
+        if var.0.skip {
+            continue;
+        }
+        let index = multiple_index(type_vec.len(), count);
+        let (ty, get_val) = quote_value(&var.0, quote!(#result_ident #index), &mut items);
         type_vec.push(ty);
-        if let Some(inlined) = var.0.inlined {
+        if let Some(inlined) = &var.0.inlined {
             ret_assign.push(quote!(#inlined = #get_val))
         } else {
             ret_tuple.push(quote!(#get_val))
         }
-        let m_str = var.1;
-        let delim = if let Some(option) = var.2 {
-            quote!(::core::option::Option::Some(#option as u8))
-        } else {
-            quote!(::core::option::Option::None)
-        };
-        quote_slice.push(quote!((#m_str, #delim)));
     }
-    let quote_tail = if let Some(tail) = tail {
-        quote! {
-            if !iter.clone().eq(#tail.bytes()) {
-                let err: ::strp::__private::alloc::string::String = iter.map(|b| b as char).collect();
-                return Err(::strp::TryParseError::ExpectedMismatch(#tail, err));
-            }
-        }
+    let tail = quote_segment(&tail, &[], None);
+    let (failed, declare_failed) = declare_failed(failed);
+    let end = !prefix;
+    let (pos, ok) = if prefix {
+        (
+            quote!(pos),
+            quote!((ok, ::strp::__private::Rest::rest(source, pos))),
+        )
     } else {
-        quote!()
+        (quote!(_), quote!(ok))
+    };
+    let type_quote = multiple_ty(&type_vec);
+    let (result_ty, result_pat, ret) = if !prefix {
+        (
+            quote!(#type_quote),
+            quote!(#result_ident),
+            quote!((#(#ret_tuple),*)),
+        )
+    } else if ret_tuple.is_empty() {
+        (
+            quote!((#type_quote, _)),
+            quote!((#result_ident, rest)),
+            quote!(rest),
+        )
+    } else {
+        (
+            quote!((#type_quote, _)),
+            quote!((#result_ident, rest)),
+            quote!(((#(#ret_tuple),*), rest)),
+        )
     };
-    let source = sparse.input;
-    let type_quote = quote!((#(#type_vec,)*));
     quote! {
         {(|| {
-            extern crate alloc;
-            let #result_ident: Result<#type_quote, ::strp::TryParseError<_>> = (|| {
+            #(#items)*
+            let #result_ident: Result<#result_ty, ::strp::TryParseError<_>> = (|| {
                 let source = &#source;
                 let slice = ::core::convert::AsRef::<[u8]>::as_ref(source);
-                let mut iter = slice.iter().cloned().peekable();
-                match ::strp::__private::ParseMultiple::parse_multiple(&mut iter, &[#(#quote_slice),*]){
-                    Ok(ok) => {
-                        #quote_tail;
-                        Ok(ok)
-                    }
-                    Err(::strp::TryParseError::Err(_)) => Err(::strp::TryParseError::Err(())),
+                #declare_failed
+                match ::strp::__private::match_multiple::<#type_quote>(
+                    slice,
+                    &[#(#quote_slice),*],
+                    &#tail,
+                    #end,
+                    &#failed,
+                ) {
+                    Ok((ok, #pos)) => Ok(#ok),
                     Err(e) => Err(e),
                 }
             })();
             match #result_ident{
                 Err(e) => Err(e),
-                Ok(#result_ident) => {
+                Ok(#result_pat) => {
                     #(#ret_assign;)*
-                    Ok((#(#ret_tuple),*))
+                    Ok(#ret)
                 }
             }
         })()}
     }
-    .into()
+}
+
+/// The matching string given to a macro taking a source string.
+enum Pattern {
+    Str(MStr),
+    /// A matching string defined through `pattern!`, written as its path.
+    Const(syn::Path),
+}
+
+impl Parse for Pattern {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let expr: Expr = input.parse()?;
+        Pattern::from_expr(&expr).ok_or_else(|| {
+            syn::Error::new_spanned(
+                &expr,
+                "expected a matching string, or the path of one defined through `pattern!`",
+            )
+        })
+    }
+}
+
+impl Pattern {
+    fn from_expr(expr: &Expr) -> Option<Self> {
+        match expr {
+            Expr::Path(path) if path.qself.is_none() => Some(Self::Const(path.path.clone())),
+            expr => MStr::from_expr(expr).map(Self::Str),
+        }
+    }
+
+    /// Generates an invocation of the macro `proc` with the matching string placed
+    /// between `before` and `after`. Matching strings defined through `pattern!` are
+    /// placed there by the macro defined alongside them.
+    fn forward(
+        &self,
+        proc: proc_macro2::TokenStream,
+        before: proc_macro2::TokenStream,
+        after: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        match self {
+            Self::Str(mstr) => {
+                let (tracked, literal) = mstr.quote();
+                quote!({ #tracked #proc!(#before #literal #after) })
+            }
+            Self::Const(path) => {
+                let defined = quote::quote_spanned!(syn::spanned::Spanned::span(path)=>
+                    ::strp::__private::defined(&#path)
+                );
+                quote!({
+                    #defined;
+                    #path!{ {#proc} {#before} {#after} }
+                })
+            }
+        }
+    }
+}
+
+impl quote::ToTokens for Pattern {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            Self::Str(mstr) => mstr.tokens.to_tokens(tokens),
+            Self::Const(path) => path.to_tokens(tokens),
+        }
+    }
 }
 
 enum MacroInput {
-    Source(Expr, String),
-    Stdin(String),
+    Source(Box<Expr>, Pattern, Args),
+    Stdin(Pattern, Args),
 }
 
 impl Parse for MacroInput {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        Ok(if let Ok(str) = input.parse::<LitStr>() {
+        Ok(if input.peek(LitStr) {
+            let str = input.parse::<LitStr>()?;
             if input.peek(Token![=>]) {
                 input.parse::<Token![=>]>()?;
                 let expr = Expr::Lit(syn::ExprLit {
                     attrs: vec![],
                     lit: syn::Lit::Str(LitStr::new(&str.value(), Span::call_site())),
                 });
-                let m_str: LitStr = input.parse()?;
-                Self::Source(expr, m_str.value())
+                Self::Source(Box::new(expr), input.parse()?, input.parse()?)
             } else {
-                Self::Stdin(str.value())
-            }
-        } else {
+                let expr = Expr::Lit(syn::ExprLit {
+                    attrs: vec![],
+                    lit: syn::Lit::Str(str),
+                });
+                let mstr = MStr::from_expr(&expr).expect("a string literal is a matching string");
+                Self::Stdin(Pattern::Str(mstr), input.parse()?)
+            }
+        } else {
             let expr = input.parse::<Expr>().expect("expected expression");
-            input.parse::<Token![=>]>()?;
-            let m_str = input
-                .parse::<LitStr>()
-                .expect("expected string literal after '=>'");
-            Self::Source(expr, m_str.value())
+            if input.parse::<Option<Token![=>]>>()?.is_some() {
+                Self::Source(Box::new(expr), input.parse()?, input.parse()?)
+            } else {
+                let pattern = Pattern::from_expr(&expr)
+                    .expect("expected '=>' followed by a matching string after the source");
+                Self::Stdin(pattern, input.parse()?)
+            }
         })
     }
 }
 
+impl MacroInput {
+    /// Splits the default given as the last argument off of the input of `name`,
+    /// returning the rest of the input alongside the default.
+    fn split_default(ts: TokenStream, err: &str, name: &str) -> (TokenStream, Expr) {
+        let mut input = syn::parse::<MacroInput>(ts).expect(err);
+        let (MacroInput::Source(_, _, args) | MacroInput::Stdin(_, args)) = &mut input;
+        let default = args.pop_default(name);
+        let ts = match input {
+            MacroInput::Source(expr, pattern, args) => {
+                let args = args.forward();
+                quote!(#expr => #pattern #args)
+            }
+            MacroInput::Stdin(pattern, args) => {
+                let args = args.forward();
+                quote!(#pattern #args)
+            }
+        };
+        (ts.into(), default)
+    }
+}
+
 macro_rules! __impl__ {
     ($ts:ident, $err:literal, $($tt:tt)*) => {
         {
             let input = syn::parse::<MacroInput>($ts).expect($err);
             match input {
-                MacroInput::Source(expr, literal) => {
-                    quote!({ $($tt)*!(#expr => #literal) })
+                MacroInput::Source(expr, pattern, args) => {
+                    let args = args.forward();
+                    pattern.forward(quote!($($tt)*), quote!(#expr =>), args)
                 }
-                MacroInput::Stdin(literal) => {
+                MacroInput::Stdin(pattern, args) => {
+                    let args = args.forward();
                     cfg_if::cfg_if! {
                         if #[cfg(feature = "std")]{
+                            let string = Ident::new("string", Span::mixed_site());
+                            let forward = pattern.forward(quote!($($tt)*), quote!(#string =>), args);
                             quote!{
                                 {
-                                    let mut string = ::std::string::String::new();
-                                    ::std::io::stdin().read_line(&mut string).unwrap();
-                                    let string = string.trim();
-                                    $($tt)*!(string => #literal)
+                                    let #string = ::strp::__private::stdin_line()
+                                        .unwrap()
+                                        .unwrap_or_default();
+                                    let #string = #string.trim();
+                                    #forward
                                 }
                             }
                         } else{
+                            let string = Ident::new("string", Span::mixed_site());
+                            let forward = pattern.forward(quote!($($tt)*), quote!(#string =>), args);
                             quote!{
                                 {
                                     const _: () = panic!("attempting to read from stdin with the `std` feature disabled!");
                                     unreachable!();
-                                    $($tt)*!("" => #literal)
+                                    let #string = "";
+                                    #forward
                                 }
                             }
                         }
@@ -381,14 +1132,15 @@ macro_rules! __impl__ {
 ///
 /// Accepts a source expression which results in a type that implements `AsRef<[u8]>`,
 /// which is then matched against a string literal in order to match a single value from
-/// the source.
+/// the source. A matching string without values evaluates to `Ok(())` if it matches,
+/// and one with multiple values is parsed as in `try_scan`.
 ///
 /// For more details read the documentation of the `strp` crate.
 ///
 /// # Examples.
 ///
 /// ```
-/// # use crate::try_parse;
+/// # use strp::try_parse;
 /// // The whole source string will be parsed into a u32.
 /// let source = "20".to_string();
 /// let v = try_parse!(source => "{}");
@@ -419,15 +1171,15 @@ macro_rules! __impl__ {
 /// // Inlines the matched value. This causes `parse` to return Result<(),_>
 /// // where the error type is deduced. If the parsing was successful, the
 /// // successfully parsed value will be put into v.
-/// let v = 0;
-/// try_parse!("u32:5" => "u32:{v}");
+/// let mut v = 0;
+/// try_parse!("u32:5" => "u32:{v}").unwrap();
 /// assert_eq!(v, 5);
 /// ```
 ///
 /// # Using stdin instead of a source string.
 ///
 /// ```no_run
-/// # use crate::try_parse;
+/// # use strp::parse;
 /// // Only available with the `std` feature.
 /// let v: f64 = parse!("{}");
 /// println!("{v}");
@@ -436,17 +1188,17 @@ macro_rules! __impl__ {
 /// # Parsing hexadecimal or binary values.
 ///
 /// ```
-/// # use crate::try_parse;
-/// let hex: u64 /* Need to specify 'u64' here, since otherwise the value will be too large. */ =
+/// # use strp::try_parse;
+/// let hex: Result<u64, _> /* Need to specify 'u64' here, since otherwise the value will be too large. */ =
 ///     try_parse!("input hex: 0x0123456789ABCDEF" => "input hex: 0x{:x}");
-/// assert_eq!(hex, 0x0123456789ABCDEF);
+/// assert_eq!(hex, Ok(0x0123456789ABCDEF));
 ///
 /// let bin = try_parse!("input bin: 0b11110001" => "input bin: 0b{:b}");
-/// assert_eq!(bin, 0b11110001);
+/// assert_eq!(bin, Ok(0b11110001));
 ///
 /// // You may also inline parsed values into `try_parse`.
-/// let bin = 0;
-/// try_parse!("input bin: 0b1111" => "input bin: {bin:b}").unwrap();
+/// let mut bin = 0;
+/// try_parse!("input bin: 0b1111" => "input bin: 0b{bin:b}").unwrap();
 /// assert_eq!(bin, 0b1111);
 /// ```
 #[proc_macro]
@@ -454,7 +1206,7 @@ pub fn try_parse(ts: TokenStream) -> TokenStream {
     __impl__!(
         ts,
         "invalid input for `try_parse`:",
-        ::strp::__private::macros::try_parse_proc
+        ::strp::__private::macros::try_match_proc
     )
     .into()
 }
@@ -462,44 +1214,98 @@ pub fn try_parse(ts: TokenStream) -> TokenStream {
 /// Interally uses `try_parse` and unwraps the result to parse a single value
 /// from a source string.
 ///
+/// If parsing fails, the panic is reported at the call site, and includes the
+/// source string, the matching string and the value where matching failed. If the
+/// source string is a literal which never matches, a compile error is reported
+/// instead, checking the values whose type is known while expanding.
+///
 /// For more details read the documentation of the `strp` crate.
 ///
 /// ```
-/// # use crate::parse;
+/// # use strp::parse;
 /// let source = "hello world!";
-/// let world = parse!(source => "hello {}!");
+/// let world: String = parse!(source => "hello {}!");
 /// assert_eq!(world, "world".to_string());
 ///
-/// // As a side effect of `parse` unwrapping and causing a possible
-/// // panic, it is not necessary to give inlined variables intitial
-/// // values
-/// let v;
+/// // Inlined values are assigned in place, and `parse` panics instead
+/// // of leaving the variable untouched if parsing fails.
+/// let mut v = String::new();
 /// parse!("value" => "{v}");
-/// asseert_eq!(v, "value".to_string());
+/// assert_eq!(v, "value".to_string());
 /// ```
 /// ```no_run
-///
-/// # use crate::parse
+/// # use strp::parse;
 /// // Uses stdin as source.
 /// let number: u32 = parse!("input number: {}");
-/// println!("number: {number})
+/// println!("number: {number}")
+/// ```
+///
+/// ```compile_fail
+/// # use strp::parse;
+/// // `-1` never parses into a `u8`.
+/// let v = parse!("value: -1" => "value: {:u8}");
 /// ```
 #[proc_macro]
 pub fn parse(ts: TokenStream) -> TokenStream {
-    let ts = __impl__!(
+    __impl__!(
         ts,
         "invalid input for `parse`:",
-        ::strp::__private::macros::try_parse_proc
+        ::strp::__private::macros::unwrap_proc
+    )
+    .into()
+}
+
+/// Interally uses `try_parse` to parse a single value from a source string,
+/// evaluating to the default given after the matching string if parsing fails.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::parse_or;
+/// let port = parse_or!("port: none" => "port: {}", 8080);
+/// assert_eq!(port, 8080);
+///
+/// let port = parse_or!("port: 443" => "port: {}", 8080);
+/// assert_eq!(port, 443);
+/// ```
+#[proc_macro]
+pub fn parse_or(ts: TokenStream) -> TokenStream {
+    let (ts, default) = MacroInput::split_default(ts, "invalid input for `parse_or`:", "parse_or");
+    let ts = __impl__!(
+        ts,
+        "invalid input for `parse_or`:",
+        ::strp::__private::macros::try_match_proc
     );
-    quote!(#ts.unwrap()).into()
+    quote!(#ts.unwrap_or_else(|_| #default)).into()
 }
 
-/// Very similar to `try_parse`, except it allows for 2 or more matched values.
+/// Interally uses `try_parse` to parse a single value from a source string,
+/// evaluating to the default value of its type if parsing fails.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::parse_or_default;
+/// let count: u32 = parse_or_default!("count: many" => "count: {}");
+/// assert_eq!(count, 0);
+/// ```
+#[proc_macro]
+pub fn parse_or_default(ts: TokenStream) -> TokenStream {
+    let ts = __impl__!(
+        ts,
+        "invalid input for `parse_or_default`:",
+        ::strp::__private::macros::try_match_proc
+    );
+    quote!(#ts.unwrap_or_default()).into()
+}
+
+/// Very similar to `try_parse`, and meant for 2 or more matched values, which are
+/// returned as a tuple. Both macros accept any number of values.
 ///
 /// For more details read the documenation of the `strp` crate.
 ///
 /// ```
-/// # use crate::try_scan;
+/// # use strp::try_scan;
 /// let source = "10, 20, 30, 40";
 /// let matched = try_scan!(source => "{}, {}, {}, {}");
 /// assert_eq!(matched, Ok((10, 20, 30, 40)));
@@ -516,7 +1322,7 @@ pub fn try_scan(ts: TokenStream) -> TokenStream {
     __impl__!(
         ts,
         "invalid input for `try_scan`:",
-        ::strp::__private::macros::try_scan_proc
+        ::strp::__private::macros::try_match_proc
     )
     .into()
 }
@@ -524,30 +1330,1386 @@ pub fn try_scan(ts: TokenStream) -> TokenStream {
 /// Interally uses `try_scan` and unwraps the result to parse multiple values
 /// from a source string.
 ///
+/// If parsing fails, the panic is reported at the call site, and includes the
+/// source string, the matching string and the value where matching failed. If the
+/// source string is a literal which never matches, a compile error is reported
+/// instead.
+///
 /// For more details read the documentation of the `strp` crate.
 ///
 /// ```
-/// # use crate::scan;
+/// # use strp::scan;
 /// let source = "10, 20, 30, 40";
 /// let matched = scan!(source => "{}, {}, {}, {}");
 /// assert_eq!(matched, (10, 20, 30, 40));
 /// ```
 ///
-/// ```
-///
-/// # use crate::scan
+/// ```no_run
+/// # use strp::scan;
 /// // Uses stdin as source.
 /// let (l, r): (u32, u32) = scan!("add {}, {}");
 /// println!("result: {}", l + r)
 /// ```
 #[proc_macro]
 pub fn scan(ts: TokenStream) -> TokenStream {
-    let ts = __impl__!(
+    __impl__!(
         ts,
         "invalid input for `scan`:",
-        ::strp::__private::macros::try_scan_proc
+        ::strp::__private::macros::unwrap_proc
+    )
+    .into()
+}
+
+/// Interally uses `try_scan` to parse multiple values from a source string,
+/// evaluating to the default given after the matching string if parsing fails.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::scan_or;
+/// let size = scan_or!("size: 20x?" => "size: {}x{}", (1, 1));
+/// assert_eq!(size, (1, 1));
+/// ```
+#[proc_macro]
+pub fn scan_or(ts: TokenStream) -> TokenStream {
+    let (ts, default) = MacroInput::split_default(ts, "invalid input for `scan_or`:", "scan_or");
+    let ts = __impl__!(
+        ts,
+        "invalid input for `scan_or`:",
+        ::strp::__private::macros::try_match_proc
     );
-    quote!(#ts.unwrap()).into()
+    quote!(#ts.unwrap_or_else(|_| #default)).into()
+}
+
+/// Interally uses `try_scan` to parse multiple values from a source string,
+/// evaluating to the default values of their types if parsing fails.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::scan_or_default;
+/// let (w, h): (u32, u32) = scan_or_default!("size: 20x?" => "size: {}x{}");
+/// assert_eq!((w, h), (0, 0));
+/// ```
+#[proc_macro]
+pub fn scan_or_default(ts: TokenStream) -> TokenStream {
+    let ts = __impl__!(
+        ts,
+        "invalid input for `scan_or_default`:",
+        ::strp::__private::macros::try_match_proc
+    );
+    quote!(#ts.unwrap_or_default()).into()
+}
+
+macro_rules! __impl_source__ {
+    ($ts:ident, $err:literal, $name:literal, $($tt:tt)*) => {
+        {
+            let input = syn::parse::<MacroInput>($ts).expect($err);
+            match input {
+                MacroInput::Source(expr, pattern, args) => {
+                    let args = args.forward();
+                    pattern.forward(quote!($($tt)*), quote!(#expr =>), args)
+                }
+                MacroInput::Stdin(..) => {
+                    panic!(concat!("`", $name, "` requires a source string, and can't read from stdin"))
+                }
+            }
+        }
+    };
+}
+
+/// Very similar to `try_parse`, except the matching string only has to match the
+/// start of the source string. The rest of the source string is returned alongside
+/// the parsed value, allowing multiple matching strings to be chained over one source.
+///
+/// The rest is returned as a `&str` for string sources, and as a `&[u8]` for byte
+/// sources. A matching string ending in a value still consumes the whole source.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::try_parse_prefix;
+/// let source = "width: 20, height: 30";
+/// let (width, rest) = try_parse_prefix!(source => "width: {},").unwrap();
+/// let (height, rest): (u32, _) = try_parse_prefix!(rest => " height: {}").unwrap();
+/// assert_eq!((width, height, rest), (20, 30, ""));
+///
+/// // Returns the rest on its own when the value is inlined.
+/// let mut width = 0;
+/// let rest = try_parse_prefix!(b"w=20;h=30" => "w={width};").unwrap();
+/// assert_eq!((width, rest), (20, &b"h=30"[..]));
+/// ```
+#[proc_macro]
+pub fn try_parse_prefix(ts: TokenStream) -> TokenStream {
+    __impl_source__!(
+        ts,
+        "invalid input for `try_parse_prefix`:",
+        "try_parse_prefix",
+        ::strp::__private::macros::try_match_prefix_proc
+    )
+    .into()
+}
+
+/// Very similar to `try_scan`, except the matching string only has to match the
+/// start of the source string. The rest of the source string is returned alongside
+/// the parsed values, allowing multiple matching strings to be chained over one source.
+///
+/// The rest is returned as a `&str` for string sources, and as a `&[u8]` for byte
+/// sources. A matching string ending in a value still consumes the whole source.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::try_scan_prefix;
+/// let source = "1 + 2; 3 + 4;";
+/// let ((a, b), rest): ((u32, u32), _) = try_scan_prefix!(source => "{} + {};").unwrap();
+/// let ((c, d), rest): ((u32, u32), _) = try_scan_prefix!(rest => " {} + {};").unwrap();
+/// assert_eq!((a + b, c + d, rest), (3, 7, ""));
+/// ```
+#[proc_macro]
+pub fn try_scan_prefix(ts: TokenStream) -> TokenStream {
+    __impl_source__!(
+        ts,
+        "invalid input for `try_scan_prefix`:",
+        "try_scan_prefix",
+        ::strp::__private::macros::try_match_prefix_proc
+    )
+    .into()
+}
+
+/// Generates the code attempting to match at a single position of `slice` for
+/// `try_find_proc` and `find_all_proc`, evaluating to the matched values paired
+/// with the end of the match.
+fn find_at(
+    m_str: &LitStr,
+    vars: &[(Var, Segment, Vec<u8>)],
+    args: &Args,
+) -> proc_macro2::TokenStream {
+    let proc = quote!(::strp::__private::macros::try_match_prefix_proc);
+    let args = args.forward();
+    let ok = if captures(vars).all(|var| var.inlined.is_some()) {
+        quote!(Ok(rest) => Some(((), slice.len() - rest.len())))
+    } else {
+        quote!(Ok((ok, rest)) => Some((ok, slice.len() - rest.len())))
+    };
+    quote! {
+        {
+            let at = &slice[pos..];
+            match #proc!(at => #m_str #args) {
+                #ok,
+                Err(_) => None,
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn try_find_proc(ts: TokenStream) -> TokenStream {
+    let sparse: Sparse = syn::parse(ts).expect("string parsing failed");
+    let m_str = LitStr::new(&sparse.mstr, Span::call_site());
+    let (vars, _) = sparse.flatten();
+    let first = vars
+        .first()
+        .map_or("", |var| var.1.leading_str())
+        .to_string();
+    let find_at = find_at(&m_str, &vars, &sparse.args);
+    let (found, ret) = if captures(&vars).all(|var| var.inlined.is_some()) {
+        (quote!(((), end)), quote!(pos..end))
+    } else {
+        (quote!((ok, end)), quote!((ok, pos..end)))
+    };
+    let source = sparse.input;
+    quote! {
+        {(|| {
+            let source = &#source;
+            let slice = ::core::convert::AsRef::<[u8]>::as_ref(source);
+            let mut start = 0;
+            while let Some(pos) = ::strp::__private::find_prefix(slice, start, #first) {
+                if let Some(#found) = #find_at {
+                    return ::core::option::Option::Some(#ret);
+                }
+                start = pos + 1;
+            }
+            ::core::option::Option::None
+        })()}
+    }
+    .into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn find_all_proc(ts: TokenStream) -> TokenStream {
+    let sparse: Sparse = syn::parse(ts).expect("string parsing failed");
+    let m_str = LitStr::new(&sparse.mstr, Span::call_site());
+    let (vars, _) = sparse.flatten();
+    let first = vars
+        .first()
+        .map_or("", |var| var.1.leading_str())
+        .to_string();
+    assert!(
+        vars.iter().all(|var| var.0.inlined.is_none()),
+        "inlined values can't be used with `find_all!`"
+    );
+    let find_at = find_at(&m_str, &vars, &sparse.args);
+    let source = sparse.input;
+    quote! {
+        {
+            let slice = ::core::convert::AsRef::<[u8]>::as_ref(&#source);
+            let mut start = 0;
+            ::core::iter::from_fn(move || {
+                while let Some(pos) = ::strp::__private::find_prefix(slice, start, #first) {
+                    if let Some((ok, end)) = #find_at {
+                        start = if end > pos { end } else { pos + 1 };
+                        return ::core::option::Option::Some((ok, pos..end));
+                    }
+                    start = pos + 1;
+                }
+                start = slice.len() + 1;
+                ::core::option::Option::None
+            })
+        }
+    }
+    .into()
+}
+
+/// Searches the source string for the first position where the matching string
+/// matches, returning the matched values alongside the range of the match.
+///
+/// Every position is tried from the start of the source, with the matching string
+/// only having to match the start of what follows it, as in `try_parse_prefix`.
+/// Positions which don't start with the leading string of the matching string are
+/// skipped without attempting to parse any values. Returns `None` if no position
+/// matched. If every value is inlined, only the range is returned.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::try_find;
+/// let line = "[info] request took 25ms to complete";
+/// let found = try_find!(line => "took {}ms");
+/// assert_eq!(found, Some((25, 15..24)));
+///
+/// let found: Option<(u32, _)> = try_find!(line => "failed after {}ms");
+/// assert_eq!(found, None);
+///
+/// let (mut a, mut b) = (0, 0);
+/// let range = try_find!("x: 1 + 2, y: 3 + 4" => "y: {a} + {b}");
+/// assert_eq!((a, b, range), (3, 4, Some(10..18)));
+/// ```
+#[proc_macro]
+pub fn try_find(ts: TokenStream) -> TokenStream {
+    __impl_source__!(
+        ts,
+        "invalid input for `try_find`:",
+        "try_find",
+        ::strp::__private::macros::try_find_proc
+    )
+    .into()
+}
+
+/// Checks whether the source string matches the matching string, returning a `bool`.
+///
+/// Values given a type are checked to parse into it, while values without a type
+/// are matched without being parsed, as in `{_}`. Values can't be inlined.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::is_match;
+/// assert!(is_match!("GET /index.html" => "GET {}"));
+/// assert!(is_match!("port: 80" => "port: {:u16}"));
+/// assert!(!is_match!("port: 80000" => "port: {:u16}"));
+/// assert!(is_match!("done" => "{(done|finished)}"));
+/// ```
+#[proc_macro]
+pub fn is_match(ts: TokenStream) -> TokenStream {
+    __impl_source__!(
+        ts,
+        "invalid input for `is_match`:",
+        "is_match",
+        ::strp::__private::macros::is_match_proc
+    )
+    .into()
+}
+
+/// Searches the source string for every position where the matching string
+/// matches, returning an iterator over the matched values alongside the range
+/// of each match.
+///
+/// Matches are searched for as in `try_find`, and never overlap. Inlined values
+/// can't be used.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::find_all;
+/// let line = "a=1, b=20, c=x, d=300";
+/// let found: Vec<(u32, _)> = find_all!(line => "={},").collect();
+/// assert_eq!(found, [(1, 1..4), (20, 6..10)]);
+///
+/// let sum: u32 = find_all!("(1,2) (3,4) (5,6)" => "({},{})")
+///     .map(|((l, r), _): ((u32, u32), _)| l * r)
+///     .sum();
+/// assert_eq!(sum, 2 + 12 + 30);
+/// ```
+#[proc_macro]
+pub fn find_all(ts: TokenStream) -> TokenStream {
+    __impl_source__!(
+        ts,
+        "invalid input for `find_all`:",
+        "find_all",
+        ::strp::__private::macros::find_all_proc
+    )
+    .into()
+}
+
+/// Creates a `StreamParser` from a matching string, which parses values from
+/// input fed to it in chunks.
+///
+/// Values are matched the same way as in `try_parse` when the matching string
+/// contains a single value, and as in `try_scan` otherwise. Inlined values can't
+/// be used, since values are returned as they're parsed. The capacity of the
+/// parser's buffer is given as a generic parameter of `StreamParser`.
+///
+/// For more details read the documentation of the `strp::stream` module.
+///
+/// ```
+/// # use strp::{stream_parser, stream::{Status, StreamParser}};
+/// let mut parser: StreamParser<u16, 16> = stream_parser!("port={};");
+/// assert_eq!(parser.feed(b"port="), Status::Incomplete);
+/// assert_eq!(parser.feed(b"8080;port=80;"), Status::Done(8080, 5));
+/// assert_eq!(parser.feed(b"port=80;"), Status::Done(80, 8));
+///
+/// // Mismatches are reported as soon as they're received.
+/// assert!(matches!(parser.feed(b"host"), Status::Error(_, 1)));
+/// ```
+#[proc_macro]
+pub fn stream_parser(ts: TokenStream) -> TokenStream {
+    let m_str: MStr = syn::parse(ts).expect("invalid input for `stream_parser`:");
+    let (vars, tail) = flatten(&m_str.value);
+    let (tracked, m_str) = m_str.quote();
+    assert!(captures(&vars).count() > 0, "missing \"{{}}\"");
+    let proc = quote!(::strp::__private::macros::try_match_proc);
+    let mut quote_slice = vec![];
+    for (var, segment, delims) in vars {
+        assert!(
+            var.inlined.is_none(),
+            "inlined values can't be used with `stream_parser!`"
+        );
+        quote_slice.push(quote_segment(&segment, &delims, Some(&var)));
+    }
+    let tail = quote_segment(&tail, &[], None);
+    quote! {{
+        #tracked
+        ::strp::stream::StreamParser::new(
+            &[#(#quote_slice),*],
+            &#tail,
+            |frame: &[u8]| #proc!(frame => #m_str).map_err(::strp::__private::erase_err),
+        )
+    }}
+    .into()
+}
+
+struct Prompt {
+    prompt: Expr,
+    m_str: MStr,
+    limit: Option<Expr>,
+}
+
+impl Parse for Prompt {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let prompt = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let m_str = input.parse()?;
+        let limit = if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self {
+            prompt,
+            m_str,
+            limit,
+        })
+    }
+}
+
+/// Writes a prompt to stdout and parses the line the user enters in response,
+/// asking again for as long as the input fails to parse.
+///
+/// Accepts a prompt expression implementing `Display`, followed by a matching string
+/// literal. Patterns with a single matched value are parsed like `try_parse`, while
+/// patterns with multiple matched values are parsed like `try_scan`. Each time the
+/// input fails to parse the error is written to stderr and the prompt is shown again.
+///
+/// Returns `Ok(None)` once stdin reaches EOF, or once the optional attempt limit
+/// given after the matching string is reached. Failing to read stdin or to write
+/// the prompt returns the I/O error. Only available with the `std` feature.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```no_run
+/// # use strp::prompt;
+/// # fn main() -> std::io::Result<()> {
+/// // Keeps asking until a valid port is entered.
+/// let port: Option<u16> = prompt!("port: " => "{}")?;
+///
+/// // Gives up after 3 failed attempts.
+/// let size: Option<(u32, u32)> = prompt!("size: " => "{}x{}", 3)?;
+///
+/// // Inlined values are assigned once the input parses.
+/// let mut name = String::new();
+/// if prompt!("name: " => "{name}")?.is_some() {
+///     println!("hello, {name}!");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro]
+pub fn prompt(ts: TokenStream) -> TokenStream {
+    let prompt: Prompt = syn::parse(ts).expect("invalid input for `prompt`:");
+    let (vars, _) = flatten(&prompt.m_str.value);
+    assert!(captures(&vars).count() > 0, "missing \"{{}}\"");
+    let proc = quote!(::strp::__private::macros::try_match_proc);
+    let (tracked, m_str) = prompt.m_str.quote();
+    let message = prompt.prompt;
+    let limit = if let Some(limit) = prompt.limit {
+        quote!(::core::option::Option::Some(#limit))
+    } else {
+        quote!(::core::option::Option::None)
+    };
+    let string = Ident::new("string", Span::mixed_site());
+    let expanded = {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "std")]{
+                quote!{
+                    {
+                        #tracked
+                        ::strp::__private::prompt(
+                            &mut ::std::io::stdin().lock(),
+                            &mut ::std::io::stdout(),
+                            &mut ::std::io::stderr(),
+                            &#message,
+                            #limit,
+                            |#string: &str| #proc!(#string => #m_str),
+                        )
+                    }
+                }
+            } else{
+                let _ = (message, limit);
+                quote!{
+                    {
+                        #tracked
+                        const _: () = panic!("attempting to prompt through stdin with the `std` feature disabled!");
+                        unreachable!();
+                        let #string = "";
+                        ::core::result::Result::<_, ()>::Ok(#proc!(#string => #m_str).ok())
+                    }
+                }
+            }
+        }
+    };
+    expanded.into()
+}
+
+/// An arm of `match_str`, being a matching string, or `_` if `m_str` is `None`.
+struct StrArm {
+    m_str: Option<MStr>,
+    body: Expr,
+}
+
+struct MatchStr {
+    input: Expr,
+    arms: Vec<StrArm>,
+}
+
+impl Parse for MatchStr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let scrutinee = Expr::parse_without_eager_brace(input)?;
+        let content;
+        syn::braced!(content in input);
+        let mut arms = vec![];
+        while !content.is_empty() {
+            let m_str = if content.parse::<Option<Token![_]>>()?.is_some() {
+                None
+            } else {
+                Some(content.parse()?)
+            };
+            content.parse::<Token![=>]>()?;
+            let body: Expr = content.parse()?;
+            let is_block = matches!(body, Expr::Block(_));
+            arms.push(StrArm { m_str, body });
+            if content.parse::<Option<Token![,]>>()?.is_none() && !is_block && !content.is_empty() {
+                return Err(content.error("expected ',' after the arm"));
+            }
+        }
+        Ok(Self {
+            input: scrutinee,
+            arms,
+        })
+    }
+}
+
+/// Generates the checks of the leading text of `arms`, given as their index and
+/// the part of their leading text which is left to check, which is known to match
+/// up until `offset`. Arms sharing leading text check it only once, and are then
+/// attempted past it. Arms whose leading text differs at `offset` can't both match,
+/// so they're grouped by it while still being attempted in order within a group.
+fn match_str_prefixes(
+    arms: &[(usize, &[u8])],
+    offset: usize,
+    attempts: &[proc_macro2::TokenStream],
+    slice: &Ident,
+) -> proc_macro2::TokenStream {
+    let mut checks = vec![];
+    let mut rest = arms;
+    while let Some(&(i, prefix)) = rest.first() {
+        if prefix.is_empty() {
+            checks.push(attempts[i].clone());
+            rest = &rest[1..];
+            continue;
+        }
+        // Arms without leading text left may match anything, so arms aren't
+        // grouped across them.
+        let len = rest
+            .iter()
+            .position(|(_, prefix)| prefix.is_empty())
+            .unwrap_or(rest.len());
+        let (run, next) = rest.split_at(len);
+        let mut firsts = vec![];
+        for (_, prefix) in run {
+            if !firsts.contains(&prefix[0]) {
+                firsts.push(prefix[0]);
+            }
+        }
+        for first in firsts {
+            let group: Vec<_> = run
+                .iter()
+                .filter(|(_, prefix)| prefix[0] == first)
+                .copied()
+                .collect();
+            let common = group.iter().fold(group[0].1, |common, (_, prefix)| {
+                let shared = common
+                    .iter()
+                    .zip(*prefix)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                &common[..shared]
+            });
+            let stripped: Vec<_> = group
+                .iter()
+                .map(|&(i, prefix)| (i, &prefix[common.len()..]))
+                .collect();
+            let inner = match_str_prefixes(&stripped, offset + common.len(), attempts, slice);
+            let end = offset + common.len();
+            let common = proc_macro2::Literal::byte_string(common);
+            checks.push(quote! {
+                if #slice.get(#offset..#end) == ::core::option::Option::Some(&#common[..]) {
+                    #inner
+                }
+            });
+        }
+        rest = next;
+    }
+    quote!(#(#checks)*)
+}
+
+/// Matches a source string against several matching strings, evaluating the arm
+/// of the first one which matches, like a `match` expression.
+///
+/// Named values in a matching string, such as `{x}`, are bound to new variables of
+/// that name within the arm, with types inferred as in the other macros. Values have
+/// to be named, or skipped as in `{_}`. A final `_` arm is required, and evaluated
+/// when no matching string matches. Arms sharing leading text check it only once.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::match_str;
+/// fn run(line: &str) -> String {
+///     match_str!(line {
+///         "move {x:i32} {y:i32}" => format!("moving by {}", x + y),
+///         "say {msg}" => {
+///             let msg: String = msg;
+///             format!("saying {msg}")
+///         }
+///         "quit" => "quitting".to_string(),
+///         _ => "unknown command".to_string(),
+///     })
+/// }
+///
+/// assert_eq!(run("move 1 2"), "moving by 3");
+/// assert_eq!(run("say hi"), "saying hi");
+/// assert_eq!(run("quit"), "quitting");
+/// assert_eq!(run("move 1"), "unknown command");
+/// ```
+#[proc_macro]
+pub fn match_str(ts: TokenStream) -> TokenStream {
+    let input: MatchStr = syn::parse(ts).expect("invalid input for `match_str`:");
+    let (default, arms) = match input.arms.split_last() {
+        Some((StrArm { m_str: None, body }, arms)) => (body, arms),
+        _ => panic!(
+            "`match_str!` requires a final `_` arm, evaluated when no matching string matches"
+        ),
+    };
+    let source = input.input;
+    let source_ident = Ident::new("source", Span::mixed_site());
+    let slice_ident = Ident::new("slice", Span::mixed_site());
+    let result_ident = Ident::new("result", Span::mixed_site());
+    let failed = Ident::new("failed", Span::mixed_site());
+    let label = syn::Lifetime::new("'arms", Span::mixed_site());
+    let mut tracked = vec![];
+    let mut leading = vec![];
+    let mut attempts = vec![];
+    for arm in arms {
+        let Some(m_str) = &arm.m_str else {
+            panic!("the `_` arm of `match_str!` has to be the last one")
+        };
+        tracked.push(m_str.quote().0);
+        let (mut vars, mut tail) = flatten(&m_str.value);
+        // The leading text is checked while choosing the arm, so the arm is only
+        // matched against what follows it.
+        let first = match vars.first_mut() {
+            Some(var) => &mut var.1,
+            None => &mut tail,
+        };
+        let skipped = first.leading_str().len();
+        leading.push(first.leading_str().to_string());
+        if skipped != 0 {
+            first.lits.remove(0);
+        }
+        let mut items = vec![];
+        let mut tys = vec![];
+        let mut binds = vec![];
+        let count = captures(&vars).count();
+        for (i, var) in captures(&vars).enumerate() {
+            let name = match &var.inlined {
+                Some(Expr::Path(path)) if path.path.get_ident().is_some() => path.path.get_ident(),
+                _ => panic!(
+                    "values in `match_str!` arms are bound to new variables, so they have to be \
+                    named as in \"{{x}}\" or skipped as in \"{{_}}\", not \"{}\"",
+                    var.text
+                ),
+            };
+            let raw = if count == 1 {
+                quote!(#result_ident)
+            } else {
+                let index = multiple_index(i, count);
+                quote!(#result_ident #index)
+            };
+            let (ty, value) = quote_value(var, raw, &mut items);
+            tys.push(ty);
+            binds.push(quote!(let #name = #value;));
+        }
+        let segments = vars
+            .iter()
+            .map(|(var, segment, delims)| quote_segment(segment, delims, Some(var)));
+        let tail = quote_segment(&tail, &[], None);
+        let matched = if count == 1 {
+            quote!(::strp::__private::match_single::<#(#tys)*>)
+        } else {
+            let tys = multiple_ty(&tys);
+            quote!(::strp::__private::match_multiple::<#tys>)
+        };
+        let body = &arm.body;
+        attempts.push(quote! {
+            {
+                #(#items)*
+                let #failed = ::core::cell::Cell::new(0usize);
+                if let Ok((#result_ident, _)) = #matched(
+                    &#slice_ident[#skipped..],
+                    &[#(#segments),*],
+                    &#tail,
+                    true,
+                    &#failed,
+                ) {
+                    #(#binds)*
+                    break #label #body;
+                }
+            }
+        });
+    }
+    let prefixed: Vec<_> = leading
+        .iter()
+        .enumerate()
+        .map(|(i, leading)| (i, leading.as_bytes()))
+        .collect();
+    let checks = match_str_prefixes(&prefixed, 0, &attempts, &slice_ident);
+    quote! {
+        {
+            #(#tracked)*
+            let #source_ident = &#source;
+            let #slice_ident = ::core::convert::AsRef::<[u8]>::as_ref(#source_ident);
+            #label: {
+                #checks
+                #default
+            }
+        }
+    }
+    .into()
+}
+
+/// What a parser does when matching fails, written after its matching string as
+/// `result`, `option` or `panic`.
+enum ParserMode {
+    Result,
+    Option,
+    Panic,
+}
+
+impl ParserMode {
+    /// The mode given by a return type written as `Result<T, E>` or `Option<T>`,
+    /// panicking for any other type.
+    fn of(output: &syn::ReturnType) -> Self {
+        let syn::ReturnType::Type(_, ty) = output else {
+            return Self::Panic;
+        };
+        let Type::Path(path) = &**ty else {
+            return Self::Panic;
+        };
+        let Some(last) = path.path.segments.last() else {
+            return Self::Panic;
+        };
+        let args = match &last.arguments {
+            syn::PathArguments::AngleBracketed(args) => args.args.len(),
+            _ => 0,
+        };
+        match (last.ident.to_string().as_str(), args) {
+            ("Result", 2) => Self::Result,
+            ("Option", 1) => Self::Option,
+            _ => Self::Panic,
+        }
+    }
+}
+
+/// The arguments of the `parser` attribute, being a matching string optionally
+/// followed by a mode.
+struct ParserAttr {
+    mstr: MStr,
+    mode: Option<ParserMode>,
+}
+
+impl Parse for ParserAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mstr = input.parse()?;
+        let mut mode = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            mode = Some(match ident.to_string().as_str() {
+                "result" => ParserMode::Result,
+                "option" => ParserMode::Option,
+                "panic" => ParserMode::Panic,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        "expected `result`, `option` or `panic`",
+                    ))
+                }
+            });
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(Self { mstr, mode })
+    }
+}
+
+/// A function without a body, written under `#[parser("...")]`.
+struct ParserFn {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    sig: syn::Signature,
+}
+
+impl Parse for ParserFn {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let sig = input.parse()?;
+        if !input.peek(Token![;]) {
+            return Err(input.error("expected `;`, since the body of a parser is generated"));
+        }
+        input.parse::<Token![;]>()?;
+        Ok(Self { attrs, vis, sig })
+    }
+}
+
+/// Turns a function declaration without a body into a function matching its first
+/// argument against the matching string given to the attribute.
+///
+/// What the function does depends on its return type:
+/// - `Result<T, E>` returns the values as in `try_scan`, converting the error into
+///   `E` through `From`, so `E` may be `strp::TryParseError` or any error it
+///   converts into.
+/// - `Option<T>` returns `None` if matching fails.
+/// - Any other type returns the values as in `scan`, panicking if matching fails.
+///
+/// The return type is only recognized when written as `Result<T, E>` or `Option<T>`.
+/// When it's written as a type alias or a renamed import, the behavior has to be
+/// given after the matching string instead, as `result`, `option` or `panic`.
+///
+/// Named values are returned like other values, unless they're written as a place
+/// behind another argument, such as `{*count}`, which they're assigned to. The
+/// matching string is added to the documentation of the function.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// use strp::{parser, TryParseError};
+///
+/// #[parser("{} -> {}")]
+/// /// An edge between two nodes.
+/// fn edge(source: &str) -> Result<(u32, u32), TryParseError<()>>;
+///
+/// #[parser("#{:x}")]
+/// fn color(source: &str) -> Option<u32>;
+///
+/// #[parser("{} + {}")]
+/// pub fn sum(source: &str) -> (i32, i32);
+///
+/// assert_eq!(edge("1 -> 2"), Ok((1, 2)));
+/// assert!(edge("1 <- 2").is_err());
+/// assert_eq!(color("#ff00ff"), Some(0xff00ff));
+/// assert_eq!(color("red"), None);
+/// assert_eq!(sum("1 + 2"), (1, 2));
+///
+/// type Parsed = Result<(u32, u32), TryParseError<()>>;
+///
+/// #[parser("{}x{}", result)]
+/// fn size(source: &str) -> Parsed;
+///
+/// assert_eq!(size("2x3"), Ok((2, 3)));
+/// ```
+#[proc_macro_attribute]
+pub fn parser(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ParserAttr { mstr, mode } =
+        syn::parse(attr).expect("expected a matching string for `parser`");
+    let ParserFn {
+        mut attrs,
+        vis,
+        sig,
+    } = syn::parse(item).expect("invalid function for `parser`");
+    let source = match sig.inputs.first() {
+        Some(syn::FnArg::Typed(arg)) => match &*arg.pat {
+            syn::Pat::Ident(pat) => pat.ident.clone(),
+            _ => panic!("the source argument of a parser has to be a plain identifier"),
+        },
+        _ => panic!("a parser needs the source string as its first argument"),
+    };
+    let sparse = Sparse {
+        input: syn::parse_quote!(#source),
+        arrow: Default::default(),
+        mstr: mstr.value.clone(),
+        args: Args::default(),
+    };
+    let mode = mode.unwrap_or_else(|| ParserMode::of(&sig.output));
+    let args: Vec<_> = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(arg) => match &*arg.pat {
+                syn::Pat::Ident(pat) => Some(&pat.ident),
+                _ => None,
+            },
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect();
+    // Names which aren't arguments only describe the values they're returned as.
+    let (mut vars, tail) = sparse.flatten();
+    for (var, _, _) in &mut vars {
+        if let Some(Expr::Path(path)) = &var.inlined {
+            if !args.iter().any(|arg| path.path.is_ident(*arg)) {
+                var.inlined = None;
+            }
+        }
+    }
+    let body = match mode {
+        ParserMode::Result => {
+            let result = try_match_impl(&sparse.input, &vars, tail, false, None);
+            quote!(#result.map_err(::core::convert::Into::into))
+        }
+        ParserMode::Option => {
+            let result = try_match_impl(&sparse.input, &vars, tail, false, None);
+            quote!(#result.ok())
+        }
+        ParserMode::Panic => unwrap_vars(&sparse, vars, tail),
+    };
+    if attrs.iter().any(|attr| attr.path.is_ident("doc")) {
+        attrs.push(syn::parse_quote!(#[doc = ""]));
+    }
+    let doc = format!("Matches the matching string `{}`.", mstr.value);
+    let tracked = mstr.quote().0;
+    quote! {
+        #(#attrs)*
+        #[doc = #doc]
+        #vis #sig {
+            #tracked
+            #body
+        }
+    }
+    .into()
+}
+
+/// A constant defined through `pattern!`, written as `pub const NAME = "...";`.
+struct PatternConst {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    name: Ident,
+    mstr: MStr,
+}
+
+impl Parse for PatternConst {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![const]>()?;
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let mstr = input.parse()?;
+        input.parse::<Option<Token![;]>>()?;
+        Ok(Self {
+            attrs,
+            vis,
+            name,
+            mstr,
+        })
+    }
+}
+
+/// Defines a constant holding a matching string, which is checked when it's
+/// defined and can be given to the other macros by its path instead of the
+/// matching string itself.
+///
+/// The constant is a `strp::Pattern`, which dereferences to the matching string
+/// so it can be used wherever it's needed at runtime. Besides the constant, a
+/// macro of the same name is defined, which the other macros use to place the
+/// matching string into their input. The macro of a public constant is exported
+/// under a hidden name, so public constants can be given to the macros by path
+/// from other crates as well.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// use strp::{pattern, scan, try_parse};
+///
+/// pattern!(const EDGE = "{} -> {}");
+/// pattern!(
+///     /// A color written as hexadecimal.
+///     pub const COLOR = concat!("#", "{:x}")
+/// );
+///
+/// let (from, to): (u32, u32) = scan!("1 -> 2" => EDGE);
+/// assert_eq!((from, to), (1, 2));
+/// assert_eq!(try_parse!("#ff00ff" => COLOR), Ok(0xff00ff));
+/// assert_eq!(EDGE, "{} -> {}");
+/// ```
+///
+/// Constants which weren't defined through `pattern!` can't be given by path.
+/// ```compile_fail
+/// # use strp::scan;
+/// const EDGE: &str = "{} -> {}";
+/// let (from, to): (u32, u32) = scan!("1 -> 2" => EDGE);
+/// ```
+#[proc_macro]
+pub fn pattern(ts: TokenStream) -> TokenStream {
+    let PatternConst {
+        attrs,
+        vis,
+        name,
+        mstr,
+    } = syn::parse(ts).expect("invalid input for `pattern`:");
+    // Checks the matching string, which panics if it's invalid.
+    flatten(&mstr.value);
+    let (tracked, literal) = mstr.quote();
+    // Exported macros are placed at the root of the crate, so their name has to
+    // be unique within it. It's derived from the matching string rather than from
+    // the order of expansion, so it stays the same across builds.
+    let (hidden, export) = match vis {
+        syn::Visibility::Public(_) => {
+            // A 64-bit FNV-1a hash, which unlike `DefaultHasher` is stable.
+            let hash = mstr
+                .value
+                .bytes()
+                .fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+                    (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+                });
+            (
+                format!("__strp_pattern_{name}_{hash:016x}"),
+                quote!(#[macro_export]),
+            )
+        }
+        _ => (format!("__strp_pattern_{name}"), quote!()),
+    };
+    let hidden = Ident::new(&hidden, name.span());
+    quote! {
+        #(#attrs)*
+        #vis const #name: ::strp::Pattern = ::strp::__private::pattern(#literal);
+
+        #tracked
+
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        #export
+        macro_rules! #hidden {
+            ({$($proc:tt)*} {$($before:tt)*} {$($after:tt)*}) => {
+                $($proc)*!($($before)* #literal $($after)*)
+            };
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        #vis use #hidden as #name;
+    }
+    .into()
+}
+
+struct ConstParse {
+    source: MStr,
+    pattern: Pattern,
+}
+
+impl Parse for ConstParse {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let source = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        Ok(Self {
+            source,
+            pattern: input.parse()?,
+        })
+    }
+}
+
+/// Evaluates the value `text` is parsed into while expanding, as a literal, or
+/// `None` if it doesn't parse.
+fn const_value(var: &Var, text: &str) -> Option<proc_macro2::TokenStream> {
+    assert!(
+        var.inlined.is_none() && var.default.is_none(),
+        "values of `const_parse` can't be inlined or have a default, since they're evaluated \
+        to constants"
+    );
+    let radix = match &var.ty {
+        VarTy::Nested(vars, tail) => {
+            let texts = match_expanded(text, vars, tail, &|var, text| {
+                const_value(var, text).is_some()
+            })?;
+            let values = captures(vars)
+                .zip(texts)
+                .map(|(var, text)| const_value(var, text));
+            let values: Vec<_> = values.collect::<Option<_>>()?;
+            return Some(quote!((#(#values),*)));
+        }
+        VarTy::Hex => 16,
+        VarTy::Binary => 2,
+        VarTy::Normal | VarTy::Class(_) => 10,
+    };
+    // Literals are unsigned, so negative values are written as a negated literal.
+    let negated = |negative: bool, lit: proc_macro2::TokenStream| match negative {
+        true => quote!(-#lit),
+        false => lit,
+    };
+    let int = |value: i128, suffix: &str| {
+        let lit = LitInt::new(
+            &format!("{}{suffix}", value.unsigned_abs()),
+            Span::call_site(),
+        );
+        negated(value < 0, quote!(#lit))
+    };
+    let float = |value: f64, ty: &str| {
+        let ty = Ident::new(if ty.is_empty() { "f64" } else { ty }, Span::call_site());
+        if value.is_nan() {
+            return quote!(::core::primitive::#ty::NAN);
+        }
+        if value.is_infinite() {
+            return negated(
+                value.is_sign_negative(),
+                quote!(::core::primitive::#ty::INFINITY),
+            );
+        }
+        let lit = syn::LitFloat::new(&format!("{:?}{ty}", value.abs()), Span::call_site());
+        negated(value.is_sign_negative(), quote!(#lit))
+    };
+    macro_rules! evaluate {
+        ($($int:ident),*) => {
+            match primitive_ty(var).as_deref() {
+                $(Some(stringify!($int)) => {
+                    let value = $int::from_str_radix(text, radix).ok()?;
+                    Some(match i128::try_from(value) {
+                        Ok(value) => int(value, stringify!($int)),
+                        Err(_) => {
+                            let lit = LitInt::new(&format!("{value}{}", stringify!($int)), Span::call_site());
+                            quote!(#lit)
+                        }
+                    })
+                })*
+                Some(ty @ ("usize" | "isize")) => {
+                    // The pointer width of the target isn't known while expanding, so
+                    // values beyond 32 bits are checked while compiling for the target.
+                    let (value, wide): (i128, _) = match ty {
+                        "usize" => (u64::from_str_radix(text, radix).ok()?.into(), "u64"),
+                        _ => (i64::from_str_radix(text, radix).ok()?.into(), "i64"),
+                    };
+                    let narrow: i128 = if ty == "usize" { u32::MAX.into() } else { i32::MAX.into() };
+                    if (-narrow - 1..=narrow).contains(&value) {
+                        return Some(int(value, ty));
+                    }
+                    let (ty, lit) = (Ident::new(ty, Span::call_site()), int(value, wide));
+                    let wide = Ident::new(wide, Span::call_site());
+                    let message = format!("{text} doesn't fit in `{ty}` on the target");
+                    let value_ident = Ident::new("value", Span::mixed_site());
+                    let const_ident = Ident::new("VALUE", Span::mixed_site());
+                    Some(quote!({
+                        const #const_ident: ::core::primitive::#ty = {
+                            let #value_ident = #lit;
+                            ::core::assert!(
+                                #value_ident as ::core::primitive::#ty as ::core::primitive::#wide == #value_ident,
+                                #message
+                            );
+                            #value_ident as ::core::primitive::#ty
+                        };
+                        #const_ident
+                    }))
+                }
+                Some(ty @ "f32") if radix == 10 => Some(float(text.parse::<f32>().ok()?.into(), ty)),
+                Some(ty @ "f64") if radix == 10 => Some(float(text.parse().ok()?, ty)),
+                Some(ty) => panic!(
+                    "`const_parse` only evaluates integers and floats, so values can't be parsed into `{ty}`"
+                ),
+                None if var.rust_ty.is_some() => panic!(
+                    "`const_parse` only evaluates integers and floats, so values can't be parsed into `{}`",
+                    var.rust_ty.as_ref().map(|ty| quote!(#ty)).unwrap_or_default()
+                ),
+                None => match i128::from_str_radix(text, radix) {
+                    Ok(value) => Some(int(value, "")),
+                    Err(_) => match u128::from_str_radix(text, radix) {
+                        Ok(value) => {
+                            let lit = LitInt::new(&value.to_string(), Span::call_site());
+                            Some(quote!(#lit))
+                        }
+                        Err(_) if radix == 10 => Some(float(text.parse().ok()?, "")),
+                        Err(_) => None,
+                    },
+                },
+            }
+        };
+    }
+    evaluate!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128)
+}
+
+/// Parses values from a source string known at compile time while expanding,
+/// evaluating to them as literals, so they can be used in constants and array
+/// lengths.
+///
+/// The source has to be a string literal, or a `concat!`, `stringify!`,
+/// `include_str!` or `env!` of literals, so configuration given through environment
+/// variables can be parsed into constants. Values are parsed into integers and floats, and
+/// any number of them may be matched as in `try_scan`. Values without a type are
+/// evaluated to literals without a suffix, so their type is inferred where
+/// they're used. A source which doesn't match is reported as a compile error, as
+/// is a `usize` or `isize` value which doesn't fit in the pointer width of the target.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::const_parse;
+/// const WIDTH: usize = const_parse!("width: 640" => "width: {}");
+/// const SIZE: (u16, u16) = const_parse!("640x480" => "{}x{}");
+/// const MASK: u8 = const_parse!("mask: 0b1010" => "mask: 0b{:b}");
+///
+/// let row = [0u8; WIDTH];
+/// assert_eq!(row.len(), 640);
+/// assert_eq!(SIZE, (640, 480));
+/// assert_eq!(MASK, 0b1010);
+/// assert_eq!(const_parse!("-1.5" => "{:f32}"), -1.5);
+/// ```
+///
+/// ```compile_fail
+/// # use strp::const_parse;
+/// const WIDTH: usize = const_parse!("width: x" => "width: {}");
+/// ```
+#[proc_macro]
+pub fn const_parse(ts: TokenStream) -> TokenStream {
+    let ConstParse { source, pattern } = syn::parse(ts).expect("invalid input for `const_parse`:");
+    let mstr = match pattern {
+        Pattern::Str(mstr) => mstr,
+        pattern => {
+            let source = &source.tokens;
+            return pattern
+                .forward(
+                    quote!(::strp::__private::macros::const_parse),
+                    quote!(#source =>),
+                    quote!(),
+                )
+                .into();
+        }
+    };
+    let (vars, tail) = flatten(&mstr.value);
+    let accept = |var: &Var, text: &str| const_value(var, text).is_some();
+    let texts = match_expanded(&source.value, &vars, &tail, &accept).unwrap_or_else(|| {
+        // Sources which aren't literals are named, since their value isn't visible.
+        let named = match syn::parse2::<LitStr>(source.tokens.clone()) {
+            Ok(_) => String::new(),
+            Err(_) => format!("`{}` is ", source.tokens.to_string().replace(" !", "!")),
+        };
+        panic!(
+            "{named}\"{}\", which doesn't match \"{}\"",
+            source.value.escape_debug(),
+            mstr.value.escape_debug()
+        )
+    });
+    let values: Vec<_> = captures(&vars)
+        .zip(texts)
+        .map(|(var, text)| const_value(var, text).expect("matched values are evaluated"))
+        .collect();
+    let value = match values.as_slice() {
+        [value] => value.clone(),
+        values => quote!((#(#values),*)),
+    };
+    if source.tracked.is_empty() && mstr.tracked.is_empty() {
+        return value.into();
+    }
+    let (source_tracked, _) = source.quote();
+    let (tracked, _) = mstr.quote();
+    quote!({
+        #source_tracked
+        #tracked
+        #value
+    })
+    .into()
+}
+
+struct Unparse {
+    mstr: Pattern,
+    args: Args,
+}
+
+impl Parse for Unparse {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            mstr: input.parse()?,
+            args: input.parse()?,
+        })
+    }
+}
+
+/// Generates the string and arguments of a `format!` call rendering the values
+/// of `vars` followed by `tail`, where `value` gives the expression of each value.
+fn render(
+    vars: &[(Var, Segment, Vec<u8>)],
+    tail: &Segment,
+    mut value: impl FnMut(&Var) -> proc_macro2::TokenStream,
+) -> (String, Vec<proc_macro2::TokenStream>) {
+    let mut fmt = String::new();
+    let push_lits = |fmt: &mut String, segment: &Segment| {
+        for lit in &segment.lits {
+            // Optional text is always rendered, and alternatives are rendered as
+            // the first one, which both match.
+            let text = match lit {
+                Lit::Str(text) | Lit::Opt(text) => text,
+                Lit::Alt(alts) => &alts[0],
+            };
+            fmt.push_str(&text.replace('{', "{{").replace('}', "}}"));
+        }
+    };
+    let mut args = vec![];
+    for (var, segment, _) in vars {
+        push_lits(&mut fmt, segment);
+        assert!(
+            !var.skip,
+            "skipped values aren't parsed, so they can't be rendered"
+        );
+        fmt.push_str("{}");
+        args.push(render_value(var, value(var)));
+    }
+    push_lits(&mut fmt, tail);
+    (fmt, args)
+}
+
+/// Generates the argument of `format!` rendering `expr` as written by `var`, such
+/// that it's parsed back into the same value.
+fn render_value(var: &Var, expr: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let rust_ty = match &var.rust_ty {
+        Some(ty) => quote!(#ty),
+        None => quote!(_),
+    };
+    match &var.ty {
+        VarTy::Normal | VarTy::Class(_) => match &var.rust_ty {
+            Some(ty) => quote!(::core::convert::identity::<&#ty>(&(#expr))),
+            None => quote!(&(#expr)),
+        },
+        VarTy::Hex => quote!(::strp::__private::Hex::<#rust_ty>::new(*&(#expr))),
+        VarTy::Binary => quote!(::strp::__private::Binary::<#rust_ty>::new(*&(#expr))),
+        VarTy::Nested(vars, tail) => {
+            let nested_ident = Ident::new("nested", Span::mixed_site());
+            let mut index = 0;
+            let (fmt, args) = render(vars, tail, |_| {
+                let field = LitInt::new(&index.to_string(), Span::call_site());
+                index += 1;
+                quote!(#nested_ident.#field)
+            });
+            quote!({
+                let #nested_ident = &(#expr);
+                ::strp::__private::alloc::format!(#fmt, #(#args),*)
+            })
+        }
+    }
+}
+
+/// Renders values into a `String` using a matching string, such that parsing the
+/// string with the same matching string gives back the values.
+///
+/// Values are given as in `format!`, either as arguments or by naming a variable or
+/// place in the matching string. Hexadecimal and binary values are rendered as
+/// written by `{:x}` and `{:b}`, nested matching strings render a tuple, optional
+/// text is always rendered, and alternatives are rendered as the first one.
+/// Skipped values can't be rendered. Only available with the `alloc` feature.
+///
+/// For more details read the documentation of the `strp` crate.
+///
+/// ```
+/// # use strp::{unparse, try_scan};
+/// let (a, b) = (10, 255);
+/// let line = unparse!("{} -> {:x}", a, b);
+/// assert_eq!(line, "10 -> ff");
+/// assert_eq!(try_scan!(line => "{} -> {:x}"), Ok((a, b)));
+///
+/// let point = (1, -2);
+/// let line = unparse!(r#"{name}: {point:"({}, {})"}"#, name = "p");
+/// assert_eq!(line, "p: (1, -2)");
+/// ```
+#[proc_macro]
+pub fn unparse(ts: TokenStream) -> TokenStream {
+    let input: Unparse = syn::parse(ts).expect("invalid input for `unparse`:");
+    let mstr = match input.mstr {
+        Pattern::Str(mstr) => mstr,
+        pattern => {
+            let args = input.args.forward();
+            return pattern
+                .forward(quote!(::strp::__private::macros::unparse), quote!(), args)
+                .into();
+        }
+    };
+    let (mut vars, tail) = flatten(&mstr.value);
+    input.args.bind(&mut vars, Expr::clone);
+    let (fmt, args) = render(&vars, &tail, |var| match &var.inlined {
+        Some(expr) => quote!(#expr),
+        None => panic!("missing argument for \"{}\"", var.text),
+    });
+    let tracked = mstr.quote().0;
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "alloc")] {
+            quote!({
+                #tracked
+                ::strp::__private::alloc::format!(#fmt, #(#args),*)
+            })
+            .into()
+        } else {
+            let _ = (tracked, fmt, args);
+            quote!({
+                const _: () = panic!("attempting to render into a `String` with the `alloc` feature disabled!");
+                unreachable!()
+            })
+            .into()
+        }
+    }
 }
 
 struct Rep(String);
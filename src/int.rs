@@ -0,0 +1,63 @@
+//! `const fn` parsers for integers, for parsing strings known at compile time,
+//! such as those given through `env!`, into constants.
+//!
+//! Each integer type has a parser for decimal, hexadecimal and binary text, which
+//! accept the same text as values written as `{}`, `{:x}` and `{:b}`: an optional
+//! `+` or `-` sign followed by digits, without a prefix such as `0x`.
+//!
+//! ```
+//! use strp::int::{parse_hex_u32, parse_u16};
+//!
+//! const PORT: u16 = match parse_u16(match option_env!("PORT") {
+//!     Some(port) => port,
+//!     None => "8080",
+//! }) {
+//!     Ok(port) => port,
+//!     Err(_) => panic!("`PORT` isn't a valid port"),
+//! };
+//! const COLOR: Result<u32, core::num::ParseIntError> = parse_hex_u32("ff00ff");
+//!
+//! assert_eq!(COLOR, Ok(0xff00ff));
+//! # assert!(option_env!("PORT").is_some() || PORT == 8080);
+//! ```
+
+use core::num::ParseIntError;
+
+macro_rules! impl_const_parsers {
+    ($($ty:ident: $dec:ident, $hex:ident, $bin:ident;)*) => {
+        $(
+            #[doc = concat!("Parses decimal text into a `", stringify!($ty), "`, as `{}` does.")]
+            #[inline]
+            pub const fn $dec(src: &str) -> Result<$ty, ParseIntError> {
+                $ty::from_str_radix(src, 10)
+            }
+
+            #[doc = concat!("Parses hexadecimal text into a `", stringify!($ty), "`, as `{:x}` does.")]
+            #[inline]
+            pub const fn $hex(src: &str) -> Result<$ty, ParseIntError> {
+                $ty::from_str_radix(src, 16)
+            }
+
+            #[doc = concat!("Parses binary text into a `", stringify!($ty), "`, as `{:b}` does.")]
+            #[inline]
+            pub const fn $bin(src: &str) -> Result<$ty, ParseIntError> {
+                $ty::from_str_radix(src, 2)
+            }
+        )*
+    };
+}
+
+impl_const_parsers!(
+    u8: parse_u8, parse_hex_u8, parse_binary_u8;
+    i8: parse_i8, parse_hex_i8, parse_binary_i8;
+    u16: parse_u16, parse_hex_u16, parse_binary_u16;
+    i16: parse_i16, parse_hex_i16, parse_binary_i16;
+    u32: parse_u32, parse_hex_u32, parse_binary_u32;
+    i32: parse_i32, parse_hex_i32, parse_binary_i32;
+    u64: parse_u64, parse_hex_u64, parse_binary_u64;
+    i64: parse_i64, parse_hex_i64, parse_binary_i64;
+    u128: parse_u128, parse_hex_u128, parse_binary_u128;
+    i128: parse_i128, parse_hex_i128, parse_binary_i128;
+    usize: parse_usize, parse_hex_usize, parse_binary_usize;
+    isize: parse_isize, parse_hex_isize, parse_binary_isize;
+);
@@ -1,6 +1,6 @@
 //! Utility library for parsing data from an input string, or stdin if built with the `std` feature.
-//! Supports no_std contexts when built without the `std` feature enabled. Requires the alloc crate.
-//! The `std` feature is enabled by default.
+//! Supports no_std contexts when built without the `std` feature enabled, and contexts without an
+//! allocator when also built without the `alloc` feature. Both features are enabled by default.
 //!
 //! Supports parsing one or multiple values from a string. Can parse primitives, Strings, or any
 //! type which derives the `TryParse` trait.
@@ -16,6 +16,7 @@
 //! `parse` and `try_parse` parses a single value from the source string,
 //! and has more cohesive errors than `scan` and `try_scan`.
 //! ```
+//! # #[cfg(feature = "alloc")] {
 //! # use strp::{try_parse, parse};
 //! // Attempts to parse  a number from `source` using `try_parse`
 //! let source = String::from("number: 30");
@@ -26,18 +27,19 @@
 //! let source = "hello, world!";
 //! let value: String = parse!(source => "hello, {}!");
 //! assert_eq!(value, "world".to_string());
+//! # }
 //! ```
 //!
-//! Neither `parse` or `try_parse` accepts anything other than one parsed
-//! value at a time. The following code will not compile.
-//!
-//! ```compile_fail
-//! let source = "some source"
-//! let ok = parse!(source => "{}"); // Ok!
-//!
-//! let err = parse!(source => "{} {}"); // Error! Attempting to parse multiple values with `parse`.
+//! Both `parse` and `try_parse` accept any number of values. A matching string
+//! without values only checks that the source matches it, and multiple values
+//! are returned as a tuple, as in `scan` and `try_scan`.
+//! ```
+//! # use strp::{parse, try_parse};
+//! let ok = try_parse!("ready" => "ready");
+//! assert_eq!(ok, Ok(()));
 //!
-//! let err = parse!(source => "some source"); // Error! Attempting to parse no values using `parse`.
+//! let (w, h): (u32, u32) = parse!("20x30" => "{}x{}");
+//! assert_eq!((w, h), (20, 30));
 //! ```
 //!
 //! # Basic `scan` and `try_scan` usage
@@ -46,6 +48,7 @@
 //! `try_parse`, but allows parsing multiple values from a single
 //! source string.
 //! ```
+//! # #[cfg(feature = "alloc")] {
 //! # use strp::{try_scan, scan};
 //! // Example of parsing 4 strings from one source string using `try_scan`
 //! let source = String::from("this is four words!");
@@ -64,24 +67,27 @@
 //! let source = "add 20, 30";
 //! let (left, right): (u32, u32) = scan!(source => "add {}, {}");
 //! assert_eq!(left + right, 50);
+//! # }
 //! ```
 //!
-//! Both `scan` or `try_scan` requires two or more parsed values at a time.
-//! The following code will not compile.
-//!
-//! ```compile_fail
-//! let source = "some source";
-//! let ok = scan!(source => "{} {}"); // Ok!
-//!
-//! let err = scan!(source => "{}"); // Error! Attempting to parse a single value with `scan`.
+//! Both `scan` and `try_scan` return a single value as is, rather than in a
+//! tuple, and `is_match` checks whether a source matches without returning any
+//! values. Values given a type are checked to parse in `is_match`, and hexadecimal
+//! and binary values without one are checked to consist of digits.
+//! ```
+//! # use strp::{is_match, try_scan};
+//! let v = try_scan!("20" => "{}");
+//! assert_eq!(v, Ok(20));
 //!
-//! let err = scan!(source => "some source"); // Error! Attempting to parse no values using `scan`.
+//! assert!(is_match!("port: 80" => "port: {:u16}"));
+//! assert!(!is_match!("port: 80000" => "port: {:u16}"));
 //! ```
 //!
 //! # Using stdin with the `std` feature.
 //!
 //! ```no_run
 //! # use strp::{try_scan, scan, try_parse, parse};
+//! # #[cfg(feature = "std")] {
 //! // Uses stdin as a source string.
 //! let name: String = parse!("hello! my name is {}.");
 //! println!("hello, {name}!");
@@ -101,11 +107,33 @@
 //!     Ok((a,b,c)) => println!("{a} + {b} = {c}"),
 //!     Err(e) => println!("an erro occured: {e:?}"),
 //! }
+//! # }
+//! ```
+//!
+//! # Prompting with the `std` feature.
+//!
+//! `prompt` writes a prompt to stdout, reads the answer from stdin, and asks
+//! again for as long as the answer fails to parse.
+//! ```no_run
+//! # use strp::prompt;
+//! # #[cfg(feature = "std")]
+//! # fn main() -> std::io::Result<()> {
+//! // Returns `Ok(None)` if stdin reaches EOF before a valid port is entered,
+//! // and the error if reading stdin fails.
+//! let port: Option<u16> = prompt!("port: " => "{}")?;
+//!
+//! // An attempt limit may be given after the matching string.
+//! let size: Option<(u32, u32)> = prompt!("size: " => "{}x{}", 3)?;
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
 //! ```
 //!
 //! # Inlining matched values.
 //!
 //! ```
+//! # #[cfg(feature = "alloc")] {
 //! # use strp::{scan, try_parse, parse, try_scan};
 //! let mut number = -1;
 //! try_parse!("input number: 20" => "input number: {number}");
@@ -151,6 +179,385 @@
 //! let (mut x, mut y) = (0, 0);
 //! let v = try_scan!("10, 10, 20, 20" => "{x}, {}, {y}, {}");
 //! assert_eq!(v, Ok((x,y)));
+//! # }
+//! ```
+//!
+//! # Inlining into fields and other places.
+//!
+//! Matched values may be inlined into any place which can be assigned to, such as a
+//! field, an index or a dereference, and not only into local variables.
+//! ```
+//! # use strp::try_scan;
+//! struct Size {
+//!     w: u32,
+//!     h: u32,
+//! }
+//!
+//! let mut size = Size { w: 0, h: 0 };
+//! try_scan!("20x30" => "{size.w}x{size.h}").unwrap();
+//! assert_eq!((size.w, size.h), (20, 30));
+//!
+//! let mut arr = [0; 2];
+//! try_scan!("1, 2" => "{arr[0]}, {arr[1]}").unwrap();
+//! assert_eq!(arr, [1, 2]);
+//! ```
+//!
+//! Expressions which can't be assigned to are rejected at compile time.
+//! ```compile_fail
+//! # use strp::try_parse;
+//! let x = 0;
+//! try_parse!("1" => "{x + 1}");
+//! ```
+//!
+//! # Assigning through arguments.
+//!
+//! Like in `format!`, arguments may be given after the matching string. A named
+//! argument is used by the value with the same name, and positional arguments are
+//! used by the values without a name, in order. Values are assigned through the
+//! arguments, which are usually `&mut` references.
+//! ```
+//! # use strp::try_scan;
+//! struct Config {
+//!     w: u32,
+//!     h: u32,
+//! }
+//!
+//! let mut cfg = Config { w: 0, h: 0 };
+//! try_scan!("20x30" => "{w}x{h}", w = &mut cfg.w, h = &mut cfg.h).unwrap();
+//! assert_eq!((cfg.w, cfg.h), (20, 30));
+//!
+//! let (mut x, mut y) = (0, 0);
+//! try_scan!("1, 2" => "{}, {}", &mut x, &mut y).unwrap();
+//! assert_eq!((x, y), (1, 2));
+//! ```
+//!
+//! # Optional and alternative text.
+//!
+//! Text inside `{[...]}` may be left out of the source string, and `{(a|b)}`
+//! matches any one of the alternatives, tried from left to right. Groups are
+//! written inside braces, so plain brackets and parentheses are still matched as
+//! text.
+//! ```
+//! # use strp::try_parse;
+//! let v = try_parse!("took 20 ms" => "took {}{[ ]}ms");
+//! assert_eq!(v, Ok(20));
+//!
+//! let v = try_parse!("took 20ms" => "took {}{[ ]}ms");
+//! assert_eq!(v, Ok(20));
+//!
+//! let v = try_parse!("OK: 10s" => "{(ok|OK)}: {}{(ms|s)}");
+//! assert_eq!(v, Ok(10));
+//! ```
+//!
+//! Values must still be separated by text which can't be left out, so matching
+//! strings where that isn't the case are rejected at compile time.
+//! ```compile_fail
+//! # use strp::try_scan;
+//! let v: Result<(u32, u32), _> = try_scan!("10 20" => "{}{[ ]}{}");
+//! ```
+//!
+//! # Character classes.
+//!
+//! A value may be limited to a class of ASCII characters by writing the class after
+//! the ':', as in `{:[a-z]}`, `{:[^,]}` or `{v:[0-9_]}`. The value then ends at the
+//! first character outside of the class instead of at the text following it, which
+//! allows values to directly follow one another. The named classes `alpha`, `alnum`,
+//! `digit`, `lower`, `upper`, `space` and `word` may be used instead, as in `{:digit}`.
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! # use strp::try_scan;
+//! let v = try_scan!("abc123def" => "{:alpha}{:digit}{}");
+//! assert_eq!(v, Ok(("abc".to_string(), 123, "def".to_string())));
+//!
+//! let v = try_scan!("key=a value,rest" => "{:word}={:[^,]},{}");
+//! assert_eq!(v, Ok(("key".to_string(), "a value".to_string(), "rest".to_string())));
+//! # }
+//! ```
+//!
+//! # Greedy values and backtracking.
+//!
+//! A value ends at the first place where the text following it matches, but if the
+//! rest of the matching string then fails to match, or a value fails to parse, later
+//! places are tried as well. Writing `{:+}`, or `{v:+}` for an inlined value, makes the
+//! value end at the last such place instead.
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! # use strp::try_scan;
+//! let v = try_scan!("archive.tar.gz" => "{}.{}");
+//! assert_eq!(v, Ok(("archive".to_string(), "tar.gz".to_string())));
+//!
+//! let v = try_scan!("archive.tar.gz" => "{:+}.{}");
+//! assert_eq!(v, Ok(("archive.tar".to_string(), "gz".to_string())));
+//!
+//! // "a" fails to parse as a u32, so the value is extended to "a.b".
+//! let v = try_scan!("a.b.7" => "{}.{}");
+//! assert_eq!(v, Ok(("a.b".to_string(), 7u32)));
+//! # }
+//! ```
+//!
+//! # Skipping values.
+//!
+//! A value written as `{_}` is matched like any other value, but is never parsed and
+//! doesn't appear in the result. This allows `parse` and `try_parse` to match a
+//! single value among any number of skipped ones.
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! # use strp::{try_parse, try_scan};
+//! let v = try_parse!("GET /index.html HTTP/1.1" => "{_} {} {_}");
+//! assert_eq!(v, Ok("/index.html".to_string()));
+//!
+//! let v = try_scan!("10 skipped 20" => "{} {_} {}");
+//! assert_eq!(v, Ok((10, 20)));
+//! # }
+//! ```
+//!
+//! # Typed values.
+//!
+//! A value may be given a type after the ':', as in `{:u16}` or `{x:f64}`, removing the
+//! need for annotations where the type can't be inferred. A type may also follow other
+//! specifiers after a space, as in `{:x u64}` or `{:digit u8}`.
+//! ```
+//! # use strp::{try_parse, try_scan};
+//! let v = try_scan!("8080 1.5" => "{:u16} {:f64}");
+//! assert_eq!(v, Ok((8080, 1.5)));
+//!
+//! let v = try_parse!("0xFFFFFFFFFF" => "0x{:x u64}");
+//! assert_eq!(v, Ok(0xFFFFFFFFFF));
+//! ```
+//!
+//! # Nested matching strings.
+//!
+//! A value may be matched by a nested matching string, written in quotes after the ':',
+//! as in `{:"{},{}"}`. The values of the nested string are returned as a tuple, and its
+//! type may be given before the quotes. Quotes within a nested string are escaped with `\`.
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! # use strp::{try_parse, try_scan};
+//! let v = try_scan!("p=1,2 n=3" => r#"p={:(i32, i32) "{},{}"} n={}"#);
+//! assert_eq!(v, Ok(((1, 2), 3)));
+//!
+//! let mut p = (String::new(), 0);
+//! try_parse!("localhost:80" => r#"{p:(String, u16) "{}:{}"}"#).unwrap();
+//! assert_eq!(p, ("localhost".to_string(), 80));
+//! # }
+//! ```
+//!
+//! # Defaults.
+//!
+//! `parse_or` and `scan_or` evaluate to the default given after the matching string when
+//! parsing fails, while `parse_or_default` and `scan_or_default` use the default value of
+//! the type. A value may also be given a default after a '=', as in `{port=8080}`, which is
+//! used when the matched text is empty or fails to parse.
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! # use strp::{parse_or, scan, scan_or_default, try_scan};
+//! let port = parse_or!("port: none" => "port: {}", 8080);
+//! assert_eq!(port, 8080);
+//!
+//! let (w, h): (u32, u32) = scan_or_default!("20x?" => "{}x{}");
+//! assert_eq!((w, h), (0, 0));
+//!
+//! let mut port = 0;
+//! let host: String = scan!("localhost:" => "{}:{port=8080}");
+//! assert_eq!((host, port), ("localhost".to_string(), 8080));
+//!
+//! let v = try_scan!("0x, 1" => "0x{:x u32=0xff}, {:u8}");
+//! assert_eq!(v, Ok((0xff, 1)));
+//! # }
+//! ```
+//!
+//! # Rendering values.
+//!
+//! `unparse` renders values into a `String` using a matching string, such that parsing it
+//! with the same matching string gives back the values. Values are given as in `format!`,
+//! and hexadecimal, binary and nested values are written as they're parsed.
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! # use strp::{try_scan, unparse};
+//! let (id, mask) = (7, 0xf0);
+//! let line = unparse!("{} -> {:x}", id, mask);
+//! assert_eq!(line, "7 -> f0");
+//! assert_eq!(try_scan!(line => "{} -> {:x}"), Ok((id, mask)));
+//!
+//! let point = (1, -2);
+//! let line = unparse!(r#"{name}: {point:"({}, {})"}"#, name = "p");
+//! assert_eq!(line, "p: (1, -2)");
+//! # }
+//! ```
+//!
+//! # Matching several matching strings.
+//!
+//! `match_str` matches a source string against several matching strings, evaluating the
+//! arm of the first one which matches. Named values are bound to new variables within
+//! the arm, and a final `_` arm is evaluated when nothing matches.
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! # use strp::match_str;
+//! let reply = match_str!("move 1 2" {
+//!     "move {x:i32} {y:i32}" => format!("moving by {}", x + y),
+//!     "say {msg:String}" => format!("saying {msg}"),
+//!     "quit" => "quitting".to_string(),
+//!     _ => "unknown command".to_string(),
+//! });
+//! assert_eq!(reply, "moving by 3");
+//! # }
+//! ```
+//!
+//! # Matching strings given at runtime.
+//!
+//! `set::PatternSet` compiles matching strings only known at runtime, such as those
+//! loaded from a configuration file, and matches a source string against all of them
+//! at once. Only the matching strings whose leading text the source starts with are
+//! attempted, and the values of each match are returned as text.
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! # use strp::set::PatternSet;
+//! let set = PatternSet::new(["join {} {}", "leave {}", "{_} joined"]).unwrap();
+//! let found = set.matches("join alice #general").next().unwrap();
+//! assert_eq!((found.index, found.captures), (0, vec!["alice", "#general"]));
+//! assert!(!set.is_match("bob left"));
+//! # }
+//! ```
+//!
+//! # Reusable parsers.
+//!
+//! The `parser` attribute turns a function declaration without a body into a function
+//! matching its first argument against a matching string, so the matching string
+//! doesn't have to be repeated wherever it's used. The return type decides whether
+//! the function returns a `Result`, an `Option`, or panics like `scan`. When the return
+//! type is an alias, this is given after the matching string as `result`, `option` or
+//! `panic` instead.
+//! ```
+//! # use strp::{parser, TryParseError};
+//! #[parser("{from} -> {to}")]
+//! fn edge(source: &str) -> Result<(u32, u32), TryParseError<()>>;
+//!
+//! #[parser("{x}, {y}")]
+//! fn point(source: &str) -> (i32, i32);
+//!
+//! assert_eq!(edge("1 -> 2"), Ok((1, 2)));
+//! assert_eq!(point("3, -4"), (3, -4));
+//! ```
+//!
+//! # Sharing matching strings.
+//!
+//! Besides string literals, matching strings may be written as a `concat!`,
+//! `stringify!`, `include_str!` or `env!` of literals. Matching strings used in several places
+//! can be defined once through `pattern!`, which checks the matching string and
+//! defines a `Pattern` constant holding it, which dereferences to a `&str`. The
+//! constant can then be given to the macros by its path, also from other crates
+//! when it's public.
+//! ```
+//! # use strp::{pattern, try_parse, try_scan};
+//! pattern!(const EDGE = "{} -> {}");
+//!
+//! let edge: Result<(u32, u32), _> = try_scan!("1 -> 2" => EDGE);
+//! assert_eq!(edge, Ok((1, 2)));
+//! assert_eq!(try_parse!("v1.5" => concat!("v", 1, ".{}")), Ok(5));
+//! ```
+//!
+//! # Evaluating at compile time.
+//!
+//! When both the source string and the matching string of `parse` or `scan` are
+//! literals, a source which never matches is reported as a compile error rather
+//! than a panic. `const_parse` goes further, parsing integers and floats while
+//! expanding, so they can be used in constants and array lengths. Its source may
+//! also be given through `env!`, and the `int` module provides `const fn` parsers
+//! for integers, for when the macros can't be used. `usize` and `isize` values
+//! beyond 32 bits are checked against the pointer width of the target, failing to
+//! compile where they don't fit.
+//! ```
+//! # use strp::const_parse;
+//! const WIDTH: usize = const_parse!("width: 640" => "width: {}");
+//! const SIZE: (u16, u16) = const_parse!("640x480" => "{}x{}");
+//! const MAJOR: u32 = const_parse!(env!("CARGO_PKG_VERSION_MAJOR") => "{}");
+//! const PORT: Result<u16, core::num::ParseIntError> = strp::int::parse_u16("8080");
+//!
+//! let row = [0u8; WIDTH];
+//! assert_eq!((row.len(), SIZE), (640, (640, 480)));
+//! assert_eq!(Ok(MAJOR), env!("CARGO_PKG_VERSION_MAJOR").parse());
+//! assert_eq!(PORT, Ok(8080));
+//! ```
+//!
+//! # Matching the start of a source string.
+//!
+//! `try_parse_prefix` and `try_scan_prefix` only require the matching string to
+//! match the start of the source string, and return the rest of the source
+//! alongside the parsed values. This allows chaining multiple matching strings
+//! over a single source.
+//! ```
+//! # use strp::{try_parse_prefix, try_scan_prefix};
+//! let source = "x: 10, y: 20; rest";
+//! let (x, rest) = try_parse_prefix!(source => "x: {},").unwrap();
+//! let (y, rest) = try_parse_prefix!(rest => " y: {};").unwrap();
+//! assert_eq!((x, y, rest), (10, 20, " rest"));
+//!
+//! // Only the rest is returned if every value is inlined.
+//! let (mut a, mut b) = (0, 0);
+//! let rest = try_scan_prefix!("1 2 3" => "{a} {b} ").unwrap();
+//! assert_eq!((a, b, rest), (1, 2, "3"));
+//! ```
+//!
+//! # Searching a source string.
+//!
+//! `try_find` returns the values and range of the first place in the source
+//! string where the matching string matches, while `find_all` returns an iterator
+//! over every such place.
+//! ```
+//! # use strp::{try_find, find_all};
+//! let line = "GET /index.html took 25ms";
+//! assert_eq!(try_find!(line => "took {}ms"), Some((25, 16..25)));
+//!
+//! let sizes: Vec<(u32, _)> = find_all!("size=10 size=x size=30 " => "size={} ").collect();
+//! assert_eq!(sizes, [(10, 0..8), (30, 15..23)]);
+//! ```
+//!
+//! # Parsing streamed input.
+//!
+//! `stream_parser` creates a parser which is fed input in chunks, and buffers
+//! them in a fixed-capacity buffer until a whole frame has been received.
+//! The buffer is stored inline, and the parser is usable without the `std` feature.
+//! ```
+//! # use strp::{stream_parser, stream::{Status, StreamParser}};
+//! let mut parser: StreamParser<(u32, u32), 32> = stream_parser!("{} {}\n");
+//! assert_eq!(parser.feed(b"10 2"), Status::Incomplete);
+//!
+//! // The number of bytes used from the last chunk is returned alongside the values.
+//! assert_eq!(parser.feed(b"0\n30 40\n"), Status::Done((10, 20), 2));
+//!
+//! // Errors also return it, so the rest of the chunk can be fed again.
+//! assert!(matches!(parser.feed(b"x 40\n30 40\n"), Status::Error(_, 5)));
+//! assert_eq!(parser.feed(b"30 40\n"), Status::Done((30, 40), 6));
+//! ```
+//!
+//! # Parsing without an allocator.
+//!
+//! Without the `alloc` feature, numbers, tuples of them and nested matching strings are
+//! parsed without allocating, and `stream_parser` and the `int` module remain available.
+//! Parsing a `String`, `unparse` and `PatternSet` need the `alloc` feature.
+//! Nested matching strings are matched against a copy of their value on the stack, so
+//! values longer than 256 bytes fail with `TryParseError::CapacityExceeded` without it.
+//! ```toml
+//! [dependencies]
+//! strp = { version = "3", default-features = false }
+//! ```
+//!
+//! With the `heapless` feature, text can be captured into a `heapless::String<N>` and raw
+//! bytes into a `heapless::Vec<u8, N>`, which fail to parse with
+//! `TryParseError::CapacityExceeded` when the value doesn't fit. Only vectors of bytes
+//! are supported, values can't be collected into a `heapless::Vec<T, N>`.
+//! ```
+//! # #[cfg(feature = "heapless")] {
+//! # use strp::{try_parse, TryParseError};
+//! use heapless::String;
+//!
+//! let name: String<8> = try_parse!("name: ferris" => "name: {}").unwrap();
+//! assert_eq!(name, "ferris");
+//!
+//! let source = "name: ferris";
+//! let name: Result<String<4>, _> = try_parse!(source => "name: {}");
+//! assert_eq!(name, Err(TryParseError::CapacityExceeded));
+//! # }
 //! ```
 //!
 //! # Hexadecimal and binary parsing.
@@ -187,20 +594,200 @@ pub extern crate strp_macros as macros;
 
 extern crate self as strp;
 
-#[cfg(test)]
+pub mod int;
+#[cfg(feature = "alloc")]
+pub mod set;
+pub mod stream;
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests;
+#[cfg(all(test, not(feature = "alloc")))]
+mod tests_no_alloc;
 
 #[doc(hidden)]
 pub mod __private {
+    #[cfg(feature = "alloc")]
     pub extern crate alloc;
     pub use macros;
 
     use crate::TryParseError;
-    use core::iter::Peekable;
+    use core::cell::Cell;
+    use core::num::ParseIntError;
+    use core::str::FromStr;
+    use strp_core::Failure;
 
     pub struct Hex<T>(T);
     pub struct Binary<T>(T);
 
+    /// Digits of the radix `R` after an optional sign, which hexadecimal and binary
+    /// values without a type are checked to consist of in `is_match`.
+    pub struct Digits<const R: u32>;
+
+    impl<const R: u32> crate::TryParse for Digits<R> {
+        type Err = ();
+
+        fn try_parse(iter: &mut impl Iterator<Item = u8>) -> Result<Self, TryParseError<()>> {
+            let mut iter = iter.peekable();
+            iter.next_if(|&b| b == b'+' || b == b'-');
+            let mut digits = 0;
+            for b in iter {
+                if !char::from(b).is_digit(R) {
+                    return Err(TryParseError::Err(()));
+                }
+                digits += 1;
+            }
+            match digits {
+                0 => Err(TryParseError::Err(())),
+                _ => Ok(Self),
+            }
+        }
+    }
+
+    /// Reads a single line from stdin, returning `None` once stdin is exhausted.
+    #[cfg(feature = "std")]
+    pub fn stdin_line() -> std::io::Result<Option<std::string::String>> {
+        let mut string = std::string::String::new();
+        match std::io::stdin().read_line(&mut string)? {
+            0 => Ok(None),
+            _ => Ok(Some(string)),
+        }
+    }
+
+    /// Writes and flushes `prompt` to `output` before reading a line from `input`,
+    /// parsing it with `parse`. Each line failing to parse has its error written
+    /// to `errors` before the prompt is shown again.
+    ///
+    /// Returns `None` once `input` is exhausted, or once `limit` lines failed to
+    /// parse. I/O errors are returned as soon as they occur.
+    #[cfg(feature = "std")]
+    pub fn prompt<T, E: core::fmt::Debug>(
+        input: &mut impl std::io::BufRead,
+        output: &mut impl std::io::Write,
+        errors: &mut impl std::io::Write,
+        prompt: &impl core::fmt::Display,
+        limit: Option<usize>,
+        mut parse: impl FnMut(&str) -> Result<T, E>,
+    ) -> std::io::Result<Option<T>> {
+        let mut string = std::string::String::new();
+        let mut attempts = 0;
+        while limit.is_none_or(|limit| attempts < limit) {
+            write!(output, "{prompt}")?;
+            output.flush()?;
+            string.clear();
+            if input.read_line(&mut string)? == 0 {
+                break;
+            }
+            match parse(string.trim()) {
+                Ok(ok) => return Ok(Some(ok)),
+                Err(e) => writeln!(errors, "{e:?}")?,
+            }
+            attempts += 1;
+        }
+        Ok(None)
+    }
+
+    /// Finds the first position from `start` onwards where `slice` starts with
+    /// `prefix`, comparing the whole prefix only where its first byte is found.
+    pub fn find_prefix(slice: &[u8], start: usize, prefix: &'static str) -> Option<usize> {
+        let prefix = prefix.as_bytes();
+        let Some(first) = prefix.first() else {
+            return (start <= slice.len()).then_some(start);
+        };
+        let mut pos = start;
+        while pos < slice.len() {
+            pos += slice[pos..].iter().position(|b| b == first)?;
+            if slice[pos..].starts_with(prefix) {
+                return Some(pos);
+            }
+            pos += 1;
+        }
+        None
+    }
+
+    /// Returns what's left of a source after a given byte offset, keeping string
+    /// sources as strings.
+    pub trait Rest {
+        type Rest: ?Sized;
+
+        fn rest(&self, offset: usize) -> &Self::Rest;
+    }
+
+    impl Rest for str {
+        type Rest = str;
+
+        #[inline(always)]
+        fn rest(&self, offset: usize) -> &str {
+            &self[offset..]
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Rest for alloc::string::String {
+        type Rest = str;
+
+        #[inline(always)]
+        fn rest(&self, offset: usize) -> &str {
+            &self[offset..]
+        }
+    }
+
+    #[cfg(feature = "heapless")]
+    impl<const N: usize> Rest for heapless::String<N> {
+        type Rest = str;
+
+        #[inline(always)]
+        fn rest(&self, offset: usize) -> &str {
+            &self[offset..]
+        }
+    }
+
+    #[cfg(feature = "heapless")]
+    impl<const N: usize> Rest for heapless::Vec<u8, N> {
+        type Rest = [u8];
+
+        #[inline(always)]
+        fn rest(&self, offset: usize) -> &[u8] {
+            &self[offset..]
+        }
+    }
+
+    impl Rest for [u8] {
+        type Rest = [u8];
+
+        #[inline(always)]
+        fn rest(&self, offset: usize) -> &[u8] {
+            &self[offset..]
+        }
+    }
+
+    impl<const N: usize> Rest for [u8; N] {
+        type Rest = [u8];
+
+        #[inline(always)]
+        fn rest(&self, offset: usize) -> &[u8] {
+            &self[offset..]
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Rest for alloc::vec::Vec<u8> {
+        type Rest = [u8];
+
+        #[inline(always)]
+        fn rest(&self, offset: usize) -> &[u8] {
+            &self[offset..]
+        }
+    }
+
+    impl<T: Rest + ?Sized> Rest for &T {
+        type Rest = T::Rest;
+
+        #[inline(always)]
+        fn rest(&self, offset: usize) -> &T::Rest {
+            T::rest(self, offset)
+        }
+    }
+
     impl<T> Hex<T> {
         #[inline(always)]
         pub fn new(val: T) -> Self {
@@ -225,6 +812,323 @@ pub mod __private {
         }
     }
 
+    /// Integers which are parsed straight from the bytes of a value, without
+    /// collecting them into a string first.
+    pub trait ParseInt: Sized {
+        /// Parses the bytes of `iter` as `from_str_radix` parses a string, returning
+        /// the same errors.
+        fn parse_int(
+            iter: &mut impl Iterator<Item = u8>,
+            radix: u32,
+        ) -> Result<Self, TryParseError<ParseIntError>>;
+    }
+
+    /// The kinds of `ParseIntError`, which can't be constructed directly.
+    #[derive(Clone, Copy)]
+    pub enum IntError {
+        Empty,
+        InvalidDigit,
+        PosOverflow,
+        NegOverflow,
+    }
+
+    impl IntError {
+        /// Text for which `from_str_radix` returns an error of this kind, for any
+        /// integer type and radix.
+        pub fn text(self) -> &'static str {
+            match self {
+                Self::Empty => "",
+                Self::InvalidDigit => "_",
+                Self::PosOverflow => "1000000000000000000000000000000000000000000000000000",
+                Self::NegOverflow => "-1000000000000000000000000000000000000000000000000000",
+            }
+        }
+    }
+
+    /// Parses an integer digit by digit, where `step` appends a digit to the value
+    /// and returns `None` on overflow, and `error` creates the errors.
+    #[inline(always)]
+    pub fn parse_int<T>(
+        iter: &mut impl Iterator<Item = u8>,
+        radix: u32,
+        signed: bool,
+        zero: T,
+        step: impl Fn(T, u32, bool) -> Option<T>,
+        error: impl Fn(IntError) -> ParseIntError,
+    ) -> Result<T, TryParseError<ParseIntError>> {
+        let first = match iter.next() {
+            Some(b) => b,
+            None => return Err(TryParseError::Err(error(IntError::Empty))),
+        };
+        let (positive, mut b) = match first {
+            b'+' | b'-' if first == b'+' || signed => match iter.next() {
+                Some(b) => (first == b'+', b),
+                // A sign on its own isn't a number.
+                None => return Err(TryParseError::Err(error(IntError::InvalidDigit))),
+            },
+            _ => (true, first),
+        };
+
+        let mut value = zero;
+        let kind = loop {
+            match (b as char).to_digit(radix) {
+                Some(digit) => match step(value, digit, positive) {
+                    Some(next) => value = next,
+                    None if positive => break IntError::PosOverflow,
+                    None => break IntError::NegOverflow,
+                },
+                None => break IntError::InvalidDigit,
+            }
+            match iter.next() {
+                Some(next) => b = next,
+                None => return Ok(value),
+            }
+        };
+
+        // The bytes before `b` are digits, so only the rest may be invalid utf8,
+        // which is reported before any other error, as when the value is decoded first.
+        if is_utf8(core::iter::once(b).chain(iter)) {
+            Err(TryParseError::Err(error(kind)))
+        } else {
+            Err(TryParseError::InvalidUtf8String)
+        }
+    }
+
+    /// Checks whether `iter` yields valid utf8, without collecting it.
+    pub fn is_utf8(iter: impl Iterator<Item = u8>) -> bool {
+        let mut buf = [0; 4];
+        let mut len = 0;
+        let mut char_len = 0;
+        for b in iter {
+            if len == 0 {
+                char_len = match b {
+                    0x00..=0x7f => continue,
+                    0xc2..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    0xf0..=0xf4 => 4,
+                    _ => return false,
+                };
+            }
+            buf[len] = b;
+            len += 1;
+            if len == char_len {
+                if core::str::from_utf8(&buf[..len]).is_err() {
+                    return false;
+                }
+                len = 0;
+            }
+        }
+        len == 0
+    }
+
+    /// Calls `f` with the bytes of `iter`, collected into a buffer of `N` bytes on
+    /// the stack, falling back to the heap for values longer than it. Without the
+    /// `alloc` feature, returns `None` for such values instead.
+    pub fn buffered<const N: usize, R>(
+        iter: &mut impl Iterator<Item = u8>,
+        f: impl FnOnce(&[u8]) -> R,
+    ) -> Option<R> {
+        let mut buf = [0; N];
+        for len in 0..N {
+            match iter.next() {
+                Some(b) => buf[len] = b,
+                None => return Some(f(&buf[..len])),
+            }
+        }
+        let Some(b) = iter.next() else {
+            return Some(f(&buf));
+        };
+        #[cfg(feature = "alloc")]
+        {
+            let mut vec = alloc::vec::Vec::from(buf);
+            vec.push(b);
+            vec.extend(iter);
+            Some(f(&vec))
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = b;
+            None
+        }
+    }
+
+    /// The most significant digits a float is parsed with. The digits past them only
+    /// decide how it's rounded through whether any of them isn't zero, which a single
+    /// digit is kept for.
+    const FLOAT_DIGITS: usize = 768;
+
+    /// The largest exponent a float is parsed with, past which every float is zero or
+    /// infinite.
+    const FLOAT_EXP: i64 = 1_000_000_000;
+
+    /// Where in a float the next byte is.
+    #[derive(Clone, Copy, PartialEq)]
+    enum FloatPart {
+        Sign,
+        Int,
+        Frac,
+        ExpSign,
+        /// After the sign of the exponent, where a digit has to follow.
+        ExpStart,
+        Exp,
+        /// `inf`, `infinity` or `nan`.
+        Word,
+    }
+
+    /// Parses the bytes of `iter` as `str::parse` parses a float, returning the same
+    /// errors. Instead of collecting every byte, zeros which don't change the value and
+    /// digits past `FLOAT_DIGITS` are dropped, so floats of any length are parsed
+    /// through a buffer on the stack.
+    pub fn parse_float<T: FromStr>(
+        iter: &mut impl Iterator<Item = u8>,
+    ) -> Result<T, TryParseError<T::Err>> {
+        fn parse<T: FromStr>(bytes: &[u8]) -> Result<T, TryParseError<T::Err>> {
+            let str = core::str::from_utf8(bytes).or(Err(TryParseError::InvalidUtf8String))?;
+            Ok(str.parse()?)
+        }
+
+        // Room for a sign, the digits, the one standing in for the dropped ones, and
+        // the exponent.
+        let mut buf = [0; FLOAT_DIGITS + 32];
+        let mut len = 0;
+        let mut part = FloatPart::Sign;
+        // Whether the float has any digits before its exponent.
+        let mut digits = false;
+        // The number of digits kept, which excludes leading zeros.
+        let mut kept = 0;
+        // Whether any of the dropped digits isn't zero.
+        let mut dropped = false;
+        // The exponent of the kept digits read as an integer, and the one written.
+        let (mut shift, mut exp, mut exp_neg) = (0i64, 0i64, false);
+        let mut empty = true;
+        while let Some(b) = iter.next() {
+            empty = false;
+            part = match (part, b) {
+                (FloatPart::Sign, b'+' | b'-') => {
+                    buf[len] = b;
+                    len += 1;
+                    FloatPart::Int
+                }
+                (FloatPart::Sign | FloatPart::Int, b'a'..=b'z' | b'A'..=b'Z') if !digits => {
+                    buf[len] = b;
+                    len += 1;
+                    FloatPart::Word
+                }
+                (FloatPart::Word, b'a'..=b'z' | b'A'..=b'Z') if len < "-infinity".len() => {
+                    buf[len] = b;
+                    len += 1;
+                    FloatPart::Word
+                }
+                (FloatPart::Sign | FloatPart::Int | FloatPart::Frac, b'0'..=b'9') => {
+                    let frac = part == FloatPart::Frac;
+                    if kept == 0 && b == b'0' {
+                        shift -= frac as i64;
+                    } else if kept < FLOAT_DIGITS {
+                        buf[len] = b;
+                        len += 1;
+                        kept += 1;
+                        shift -= frac as i64;
+                    } else {
+                        dropped |= b != b'0';
+                        shift += !frac as i64;
+                    }
+                    digits = true;
+                    if frac {
+                        FloatPart::Frac
+                    } else {
+                        FloatPart::Int
+                    }
+                }
+                (FloatPart::Sign | FloatPart::Int, b'.') => FloatPart::Frac,
+                (FloatPart::Int | FloatPart::Frac, b'e' | b'E') if digits => FloatPart::ExpSign,
+                (FloatPart::ExpSign, b'+' | b'-') => {
+                    exp_neg = b == b'-';
+                    FloatPart::ExpStart
+                }
+                (FloatPart::ExpSign | FloatPart::ExpStart | FloatPart::Exp, b'0'..=b'9') => {
+                    exp = (10 * exp + (b - b'0') as i64).min(FLOAT_EXP);
+                    FloatPart::Exp
+                }
+                _ => {
+                    // Only the rest may be invalid utf8, which is reported before the
+                    // float being invalid, as when the value is decoded first.
+                    if !is_utf8(core::iter::once(b).chain(iter)) {
+                        return Err(TryParseError::InvalidUtf8String);
+                    }
+                    return parse(b"-");
+                }
+            };
+        }
+
+        match part {
+            _ if empty => parse(b""),
+            FloatPart::Word => parse(&buf[..len]),
+            FloatPart::Int | FloatPart::Frac | FloatPart::Exp if digits => {
+                if kept == 0 {
+                    buf[len] = b'0';
+                    return parse(&buf[..=len]);
+                }
+                if dropped {
+                    buf[len] = b'1';
+                    len += 1;
+                    shift -= 1;
+                }
+                let exp = (shift + if exp_neg { -exp } else { exp }).clamp(-FLOAT_EXP, FLOAT_EXP);
+                buf[len] = b'e';
+                len += 1;
+                if exp < 0 {
+                    buf[len] = b'-';
+                    len += 1;
+                }
+                let mut exp = exp.unsigned_abs();
+                let start = len;
+                loop {
+                    buf[len] = b'0' + (exp % 10) as u8;
+                    len += 1;
+                    exp /= 10;
+                    if exp == 0 {
+                        break;
+                    }
+                }
+                buf[start..len].reverse();
+                parse(&buf[..len])
+            }
+            _ => parse(b"-"),
+        }
+    }
+
+    macro_rules! impl_parse_int {
+        ($($ty:ident),*) => {
+            $(
+                impl ParseInt for $ty {
+                    #[allow(unused_comparisons)]
+                    fn parse_int(
+                        iter: &mut impl Iterator<Item = u8>,
+                        radix: u32,
+                    ) -> Result<Self, TryParseError<ParseIntError>> {
+                        parse_int(
+                            iter,
+                            radix,
+                            $ty::MIN < 0,
+                            0,
+                            |value: $ty, digit, positive| {
+                                let value = value.checked_mul(radix as $ty)?;
+                                if positive {
+                                    value.checked_add(digit as $ty)
+                                } else {
+                                    value.checked_sub(digit as $ty)
+                                }
+                            },
+                            |kind| kind.text().parse::<$ty>().unwrap_err(),
+                        )
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_parse_int!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
     macro_rules! impl_hex__and_binary_for_primitives {
         ($($ty:ty),*) => {
             $(
@@ -234,10 +1138,7 @@ pub mod __private {
                     fn try_parse(
                         iter: &mut impl Iterator<Item = u8>,
                     ) -> Result<Self, TryParseError<Self::Err>> {
-                        let vec = iter.collect::<alloc::vec::Vec<u8>>();
-                        let str = core::str::from_utf8(&vec)
-                            .or(Err(TryParseError::InvalidUtf8String))?;
-                        Ok(Self(<$ty>::from_str_radix(&str, 16)?))
+                        Ok(Self(<$ty as ParseInt>::parse_int(iter, 16)?))
                     }
                 }
 
@@ -253,10 +1154,7 @@ pub mod __private {
                     fn try_parse(
                         iter: &mut impl Iterator<Item = u8>,
                     ) -> Result<Self, TryParseError<Self::Err>> {
-                        let vec = iter.collect::<alloc::vec::Vec<u8>>();
-                        let str = core::str::from_utf8(&vec)
-                            .or(Err(TryParseError::InvalidUtf8String))?;
-                        Ok(Self(<$ty>::from_str_radix(&str, 2)?))
+                        Ok(Self(<$ty as ParseInt>::parse_int(iter, 2)?))
                     }
                 }
 
@@ -265,6 +1163,29 @@ pub mod __private {
                         bin.0
                     }
                 }
+
+                // Negative values are written with a sign, as `from_str_radix` expects.
+                impl core::fmt::Display for Hex<$ty> {
+                    #[allow(unused_comparisons)]
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        if self.0 < 0 {
+                            write!(f, "-{:x}", (0 as $ty).wrapping_sub(self.0))
+                        } else {
+                            write!(f, "{:x}", self.0)
+                        }
+                    }
+                }
+
+                impl core::fmt::Display for Binary<$ty> {
+                    #[allow(unused_comparisons)]
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        if self.0 < 0 {
+                            write!(f, "-{:b}", (0 as $ty).wrapping_sub(self.0))
+                        } else {
+                            write!(f, "{:b}", self.0)
+                        }
+                    }
+                }
             )*
 
         };
@@ -274,94 +1195,321 @@ pub mod __private {
         u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize
     );
 
+    pub use strp_core::{Class, Lit, Segment};
+
+    /// Matches `segments` followed by `tail` against `slice` through
+    /// `strp_core::match_values`, calling `parse` with the index and bytes of each
+    /// value as it's found, except for skipped values. If `end` is set the match
+    /// has to reach the end of `slice`.
+    ///
+    /// Returns where the match ends, or the first error which occured if no way of
+    /// splitting `slice` into values matches. The index of the segment the error
+    /// occured in is stored in `failed`, being `segments.len()` for the trailing string.
     #[inline(always)]
-    pub fn parse_single<S: ::strp::TryParse>(
-        iter: &mut Peekable<impl Iterator<Item = u8> + Clone>,
-        m_str: &'static str,
-        delim: Option<u8>,
-    ) -> Result<S, TryParseError<S::Err>> {
-        let cmp = m_str.bytes();
-        let iter_err = iter.clone();
-        if iter.by_ref().take(cmp.len()).eq(cmp) {
-            if let Some(delim) = delim {
-                let iter = iter.by_ref();
-                let mut iter = core::iter::from_fn(|| iter.next_if(|e| *e != delim));
-                S::try_parse(&mut iter)
-            } else {
-                S::try_parse(iter)
+    pub fn match_values<E>(
+        slice: &[u8],
+        segments: &[Segment<'static>],
+        tail: &Segment<'static>,
+        end: bool,
+        failed: &Cell<usize>,
+        mut parse: impl FnMut(usize, &[u8]) -> Result<(), TryParseError<E>>,
+    ) -> Result<usize, TryParseError<E>> {
+        strp_core::match_values(slice, segments, tail, end, |i, range| {
+            parse(i, &slice[range])
+        })
+        .map_err(|(i, failure)| {
+            failed.set(i);
+            match failure {
+                Failure::Mismatch(m_str, pos) => TryParseError::ExpectedMismatch(m_str, pos),
+                Failure::Value(err) => err,
             }
-        } else {
-            let mut iter_err = iter_err.take(m_str.len()).peekable();
-            return if let Some(delim) = delim {
-                let iter_err = core::iter::from_fn(|| iter_err.next_if(|e| *e != delim));
-                Err(TryParseError::ExpectedMismatch(
-                    m_str,
-                    iter_err.map(|b| b as char).collect(),
-                ))
-            } else {
-                Err(TryParseError::ExpectedMismatch(
-                    m_str,
-                    iter_err.map(|b| b as char).collect(),
-                ))
-            };
+        })
+    }
+
+    /// Matches and parses a single value, returning it alongside where the match ends.
+    #[inline(always)]
+    pub fn match_single<S: ::strp::TryParse>(
+        slice: &[u8],
+        segments: &[Segment<'static>],
+        tail: &Segment<'static>,
+        end: bool,
+        failed: &Cell<usize>,
+    ) -> Result<(S, usize), TryParseError<S::Err>> {
+        let mut value = None;
+        let pos = match_values(slice, segments, tail, end, failed, |_, bytes| {
+            value = Some(S::try_parse(&mut bytes.iter().copied())?);
+            Ok(())
+        })?;
+        Ok((value.expect("a successful match parses every value"), pos))
+    }
+
+    /// Matches and parses multiple values, returning them alongside where the match ends.
+    #[inline(always)]
+    pub fn match_multiple<T: ParseMultiple>(
+        slice: &[u8],
+        segments: &[Segment<'static>],
+        tail: &Segment<'static>,
+        end: bool,
+        failed: &Cell<usize>,
+    ) -> Result<(T, usize), TryParseError<()>> {
+        let mut slots = T::slots();
+        let pos = match_values(slice, segments, tail, end, failed, |i, bytes| {
+            T::parse_nth(&mut slots, i, bytes)
+        })?;
+        Ok((T::from_slots(slots), pos))
+    }
+
+    /// A matching string nested in a value, written as `{:"{},{}"}`.
+    pub trait Pattern {
+        const SEGMENTS: &'static [Segment<'static>];
+        const TAIL: &'static Segment<'static>;
+    }
+
+    /// Values parsed from the matching string `P` nested in a value.
+    pub struct Nested<P, T>(T, core::marker::PhantomData<P>);
+
+    impl<P, T> Nested<P, T> {
+        #[inline(always)]
+        pub fn into_inner(self) -> T {
+            self.0
         }
     }
 
-    pub trait ParseMultiple: Sized {
-        fn parse_multiple(
-            iter: &mut Peekable<impl Iterator<Item = u8> + Clone>,
-            sparse_data: &[(&'static str, Option<u8>)],
-        ) -> Result<Self, TryParseError<()>>;
+    impl<P: Pattern, T: ParseMultiple> crate::TryParse for Nested<P, T> {
+        type Err = ();
+
+        fn try_parse(iter: &mut impl Iterator<Item = u8>) -> Result<Self, TryParseError<()>> {
+            let failed = Cell::new(0);
+            let (value, _) = buffered::<256, _>(iter, |bytes| {
+                match_multiple::<T>(bytes, P::SEGMENTS, P::TAIL, true, &failed)
+            })
+            .unwrap_or(Err(TryParseError::CapacityExceeded))?;
+            Ok(Self(value, core::marker::PhantomData))
+        }
     }
 
-    impl<T: strp::TryParse, const LEN: usize> ParseMultiple for [T; LEN] {
+    /// A value with a default, which is `None` when the matched text is empty or
+    /// fails to parse. Parsing it never fails.
+    pub struct Or<T>(Option<T>);
+
+    impl<T> Or<T> {
         #[inline(always)]
-        fn parse_multiple(
-            iter: &mut Peekable<impl Iterator<Item = u8> + Clone>,
-            sparse_data: &[(&'static str, Option<u8>)],
-        ) -> Result<Self, TryParseError<()>> {
-            assert!(LEN == sparse_data.len());
-            let mut array: [T; LEN] = unsafe { core::mem::MaybeUninit::zeroed().assume_init() };
-            for i in 0..LEN {
-                let cur = unsafe { sparse_data.get_unchecked(i) };
-                *unsafe { array.get_unchecked_mut(i) } = match parse_single::<T>(iter, cur.0, cur.1)
-                {
-                    Ok(ok) => ok,
-                    Err(strp::TryParseError::Err(_)) => return Err(TryParseError::Err(())),
-                    Err(TryParseError::InvalidUtf8String) => {
-                        return Err(TryParseError::InvalidUtf8String)
+        pub fn into_inner(self) -> Option<T> {
+            self.0
+        }
+    }
+
+    impl<T: crate::TryParse> crate::TryParse for Or<T> {
+        type Err = T::Err;
+
+        fn try_parse(iter: &mut impl Iterator<Item = u8>) -> Result<Self, TryParseError<T::Err>> {
+            let mut iter = iter.peekable();
+            if iter.peek().is_none() {
+                return Ok(Self(None));
+            }
+            Ok(Self(T::try_parse(&mut iter).ok()))
+        }
+    }
+
+    /// Panics with a message describing why `input` failed to match `pattern`, where
+    /// `values` are the values of the pattern as written, and `failed` is the index of
+    /// the segment the error occured in.
+    #[cold]
+    #[track_caller]
+    pub fn parse_failed<E: core::fmt::Debug>(
+        err: TryParseError<E>,
+        input: &[u8],
+        pattern: &str,
+        values: &[&str],
+        failed: usize,
+    ) -> ! {
+        /// The input as lossily decoded utf8, cut off after `MAX_INPUT` chars.
+        struct Input<'a>(&'a [u8]);
+
+        impl core::fmt::Display for Input<'_> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                const MAX_INPUT: usize = 64;
+
+                let mut chars = 0;
+                for chunk in self.0.utf8_chunks() {
+                    let replacement = (!chunk.invalid().is_empty()).then_some('\u{FFFD}');
+                    for c in chunk.valid().chars().chain(replacement) {
+                        if chars == MAX_INPUT {
+                            return f.write_str("...");
+                        }
+                        write!(f, "{c}")?;
+                        chars += 1;
                     }
-                    Err(TryParseError::ExpectedMismatch(l, r)) => {
-                        return Err(TryParseError::ExpectedMismatch(l, r))
+                }
+                Ok(())
+            }
+        }
+
+        /// Where in the matching string the error occured.
+        struct Place<'a> {
+            mismatch: bool,
+            value: Option<&'a &'a str>,
+            failed: usize,
+        }
+
+        impl core::fmt::Display for Place<'_> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self.value {
+                    Some(value) if self.mismatch => {
+                        write!(
+                            f,
+                            "in the text before value {} (`{value}`)",
+                            self.failed + 1
+                        )
                     }
+                    Some(value) => write!(f, "in value {} (`{value}`)", self.failed + 1),
+                    None => f.write_str("in the trailing text"),
                 }
             }
-            Ok(array)
+        }
+
+        let place = Place {
+            mismatch: matches!(err, TryParseError::ExpectedMismatch(..)),
+            value: values.get(failed),
+            failed,
+        };
+        match err.found(input) {
+            Some(found) => panic!(
+                "failed to match \"{}\" with \"{pattern}\" {place}: {err:?}, found \"{}\"",
+                Input(input),
+                Input(found)
+            ),
+            None => panic!(
+                "failed to match \"{}\" with \"{pattern}\" {place}: {err:?}",
+                Input(input)
+            ),
         }
     }
 
+    /// Creates the constant defined through `pattern!`.
+    pub const fn pattern(m_str: &'static str) -> crate::Pattern {
+        crate::Pattern(m_str)
+    }
+
+    /// Implemented by the constants which may be given to the macros by path.
+    #[diagnostic::on_unimplemented(
+        message = "matching strings given by path have to be defined through `strp::pattern!`",
+        label = "this is a `{Self}` rather than a `strp::Pattern`",
+        note = "define it as `pattern!(const NAME = \"...\");`, or write the matching string itself"
+    )]
+    pub trait Defined {}
+
+    impl Defined for crate::Pattern {}
+
+    /// Checks that a constant given by path was defined through `pattern!`, which
+    /// reports a clearer error than the missing macro of the same name.
+    pub const fn defined<T: Defined>(_: &T) {}
+
+    /// Converts the error of parsing a single value into the error of parsing
+    /// multiple values.
+    #[inline(always)]
+    pub fn erase_err<E>(err: TryParseError<E>) -> TryParseError<()> {
+        match err {
+            TryParseError::Err(_) => TryParseError::Err(()),
+            TryParseError::InvalidUtf8String => TryParseError::InvalidUtf8String,
+            TryParseError::CapacityExceeded => TryParseError::CapacityExceeded,
+            TryParseError::ExpectedMismatch(l, r) => TryParseError::ExpectedMismatch(l, r),
+        }
+    }
+
+    pub trait ParseMultiple: Sized {
+        /// The number of values parsed.
+        const LEN: usize;
+
+        /// Storage for the values parsed so far.
+        type Slots;
+
+        fn slots() -> Self::Slots;
+
+        /// Parses the `i`th value from `bytes` into `slots`.
+        fn parse_nth(
+            slots: &mut Self::Slots,
+            i: usize,
+            bytes: &[u8],
+        ) -> Result<(), TryParseError<()>>;
+
+        /// Takes the values out of `slots`, once every value has been parsed.
+        fn from_slots(slots: Self::Slots) -> Self;
+    }
+
+    impl<T: strp::TryParse, const LEN: usize> ParseMultiple for [T; LEN] {
+        const LEN: usize = LEN;
+
+        type Slots = [Option<T>; LEN];
+
+        #[inline(always)]
+        fn slots() -> Self::Slots {
+            core::array::from_fn(|_| None)
+        }
+
+        #[inline(always)]
+        fn parse_nth(
+            slots: &mut Self::Slots,
+            i: usize,
+            bytes: &[u8],
+        ) -> Result<(), TryParseError<()>> {
+            slots[i] = Some(T::try_parse(&mut bytes.iter().copied()).map_err(erase_err)?);
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn from_slots(slots: Self::Slots) -> Self {
+            slots.map(|value| value.expect("a successful match parses every value"))
+        }
+    }
+
+    // Used by matching strings without values, which only check that they match.
+    impl ParseMultiple for () {
+        const LEN: usize = 0;
+
+        type Slots = ();
+
+        #[inline(always)]
+        fn slots() -> Self::Slots {}
+
+        #[inline(always)]
+        fn parse_nth(_: &mut Self::Slots, _: usize, _: &[u8]) -> Result<(), TryParseError<()>> {
+            unreachable!("there are no values to parse")
+        }
+
+        #[inline(always)]
+        fn from_slots(_: Self::Slots) -> Self {}
+    }
+
     macro_rules! impl_sparse_multiple_tuple {
-        ($first:ident, $($rest:ident),+; $size:expr;) => {
-            impl<$first: ::strp::TryParse, $($rest: ::strp::TryParse),+> ParseMultiple for ($first, $($rest),+) {
+        ($first:ident $(, $rest:ident)*; $size:expr;) => {
+            impl<$first: ::strp::TryParse, $($rest: ::strp::TryParse),*> ParseMultiple for ($first, $($rest),*) {
+                const LEN: usize = $size;
+
+                type Slots = (Option<$first>, $(Option<$rest>),*);
 
                 #[inline(always)]
-                fn parse_multiple(
-                    iter: &mut Peekable<impl Iterator<Item = u8> + Clone>,
-                    sparse_data: &[(&'static str, Option<u8>)],
-                ) -> Result<Self, TryParseError<()>> {
-                    assert!($size == sparse_data.len());
-                    Ok(
-                        macros::rep!($size[match parse_single(iter, sparse_data[#].0, sparse_data[#].1){
-                            Ok(ok) => ok,
-                            Err(strp::TryParseError::Err(_)) => return Err(TryParseError::Err(())),
-                            Err(TryParseError::InvalidUtf8String) => {
-                                return Err(TryParseError::InvalidUtf8String)
-                            }
-                            Err(TryParseError::ExpectedMismatch(l, r)) => {
-                                return Err(TryParseError::ExpectedMismatch(l, r))
-                            }
-                        }])
-                    )
+                fn slots() -> Self::Slots {
+                    macros::rep!($size[None])
+                }
+
+                #[inline(always)]
+                fn parse_nth(
+                    slots: &mut Self::Slots,
+                    i: usize,
+                    bytes: &[u8],
+                ) -> Result<(), TryParseError<()>> {
+                    macros::rep!($size[if i == # {
+                        slots.# = Some(
+                            ::strp::TryParse::try_parse(&mut bytes.iter().copied()).map_err(erase_err)?
+                        );
+                    }]);
+                    Ok(())
+                }
+
+                #[inline(always)]
+                fn from_slots(slots: Self::Slots) -> Self {
+                    macros::rep!($size[slots.#.expect("a successful match parses every value")])
                 }
             }
         };
@@ -382,9 +1530,100 @@ pub mod __private {
     impl_sparse_multiple_tuple!(A,B,C,D; 4;);
     impl_sparse_multiple_tuple!(A,B,C; 3;);
     impl_sparse_multiple_tuple!(A,B; 2;);
+    impl_sparse_multiple_tuple!(A; 1;);
+
+    /// Values which don't fit in a single tuple, parsed as the values of `A`
+    /// followed by the values of `B`.
+    pub struct Chain<A, B>(pub A, pub B);
+
+    impl<A: ParseMultiple, B: ParseMultiple> ParseMultiple for Chain<A, B> {
+        const LEN: usize = A::LEN + B::LEN;
+
+        type Slots = (A::Slots, B::Slots);
+
+        #[inline(always)]
+        fn slots() -> Self::Slots {
+            (A::slots(), B::slots())
+        }
+
+        #[inline(always)]
+        fn parse_nth(
+            slots: &mut Self::Slots,
+            i: usize,
+            bytes: &[u8],
+        ) -> Result<(), TryParseError<()>> {
+            if i < A::LEN {
+                A::parse_nth(&mut slots.0, i, bytes)
+            } else {
+                B::parse_nth(&mut slots.1, i - A::LEN, bytes)
+            }
+        }
+
+        #[inline(always)]
+        fn from_slots(slots: Self::Slots) -> Self {
+            Chain(A::from_slots(slots.0), B::from_slots(slots.1))
+        }
+    }
 }
 
-pub use macros::{parse, scan, try_parse, try_scan};
+pub use macros::{
+    const_parse, find_all, is_match, match_str, parse, parse_or, parse_or_default, parser, pattern,
+    prompt, scan, scan_or, scan_or_default, stream_parser, try_find, try_parse, try_parse_prefix,
+    try_scan, try_scan_prefix, unparse,
+};
+
+/// A matching string defined through `pattern!`, which was checked when it was
+/// defined.
+///
+/// Dereferences to the matching string, so it can be used wherever the matching
+/// string is needed at runtime.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pattern(&'static str);
+
+impl Pattern {
+    /// Returns the matching string.
+    pub const fn as_str(self) -> &'static str {
+        self.0
+    }
+}
+
+impl core::ops::Deref for Pattern {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl AsRef<str> for Pattern {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl PartialEq<str> for Pattern {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Pattern {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl core::fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl core::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
 
 /// Allows a type to be parsed through the `try_parse`, `parse`, `try_scan` and `scan` macros.
 pub trait TryParse
@@ -398,48 +1637,118 @@ where
     fn try_parse(iter: &mut impl Iterator<Item = u8>) -> Result<Self, TryParseError<Self::Err>>;
 }
 
-macro_rules! impl_from_str_tys {
+// Numbers are parsed straight from the bytes of the value, without allocating.
+macro_rules! impl_int_tys {
     ($($ty:ty),*) => {
         $(
-            impl TryParse for $ty where Self: ::core::str::FromStr {
-                type Err = <Self as ::core::str::FromStr>::Err;
+            impl TryParse for $ty {
+                type Err = core::num::ParseIntError;
 
                 fn try_parse(
-                    iter: &mut impl  core::iter::Iterator<Item = u8>,
+                    iter: &mut impl core::iter::Iterator<Item = u8>,
                 ) -> Result<Self, TryParseError<Self::Err>> {
-                    Ok(::core::str::FromStr::from_str(
-                        core::str::from_utf8(&iter.collect::<__private::alloc::vec::Vec<u8>>()).or(Err(TryParseError::InvalidUtf8String))?,
-                    )?)
+                    __private::ParseInt::parse_int(iter, 10)
                 }
             }
         )*
     };
 }
 
-impl_from_str_tys!(
-    u8,
-    i8,
-    u16,
-    i16,
-    u32,
-    i32,
-    u64,
-    i64,
-    u128,
-    i128,
-    f32,
-    f64,
-    __private::alloc::string::String
-);
+impl_int_tys!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128);
+
+macro_rules! impl_float_tys {
+    ($($ty:ty),*) => {
+        $(
+            impl TryParse for $ty {
+                type Err = core::num::ParseFloatError;
+
+                fn try_parse(
+                    iter: &mut impl core::iter::Iterator<Item = u8>,
+                ) -> Result<Self, TryParseError<Self::Err>> {
+                    __private::parse_float(iter)
+                }
+            }
+        )*
+    };
+}
+
+impl_float_tys!(f32, f64);
+
+#[cfg(feature = "alloc")]
+impl TryParse for __private::alloc::string::String {
+    type Err = core::convert::Infallible;
+
+    fn try_parse(
+        iter: &mut impl core::iter::Iterator<Item = u8>,
+    ) -> Result<Self, TryParseError<Self::Err>> {
+        Self::from_utf8(iter.collect()).or(Err(TryParseError::InvalidUtf8String))
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> TryParse for heapless::String<N> {
+    type Err = core::convert::Infallible;
+
+    fn try_parse(
+        iter: &mut impl core::iter::Iterator<Item = u8>,
+    ) -> Result<Self, TryParseError<Self::Err>> {
+        let mut vec = heapless::Vec::<u8, N>::new();
+        let mut exceeded = false;
+        // Every byte is checked to be utf8, even past the capacity, so invalid utf8 is
+        // reported over the value not fitting.
+        let mut pending = heapless::Vec::<u8, 4>::new();
+        for b in iter {
+            exceeded |= vec.push(b).is_err();
+            let _ = pending.push(b);
+            match core::str::from_utf8(&pending) {
+                Ok(_) => pending.clear(),
+                Err(err) if err.error_len().is_none() => {}
+                Err(_) => return Err(TryParseError::InvalidUtf8String),
+            }
+        }
+        if !pending.is_empty() {
+            Err(TryParseError::InvalidUtf8String)
+        } else if exceeded {
+            Err(TryParseError::CapacityExceeded)
+        } else {
+            Self::from_utf8(vec).or(Err(TryParseError::InvalidUtf8String))
+        }
+    }
+}
+
+// Captures the bytes of the value as they are, without decoding them as utf8. Only
+// bytes are supported, a list of values can't be parsed into a `heapless::Vec<T, N>`.
+#[cfg(feature = "heapless")]
+impl<const N: usize> TryParse for heapless::Vec<u8, N> {
+    type Err = core::convert::Infallible;
+
+    fn try_parse(
+        iter: &mut impl core::iter::Iterator<Item = u8>,
+    ) -> Result<Self, TryParseError<Self::Err>> {
+        let mut vec = Self::new();
+        for b in iter {
+            vec.push(b).or(Err(TryParseError::CapacityExceeded))?;
+        }
+        Ok(vec)
+    }
+}
 
 /// Generic error type for parsing.
+///
+/// More variants may be added in the future, so matching on it requires a wildcard arm.
 #[derive(PartialEq)]
+#[non_exhaustive]
 pub enum TryParseError<T> {
     /// The pattern in the source string doesn't match
     /// the given pattern.
-    ExpectedMismatch(&'static str, __private::alloc::string::String),
+    ///
+    /// Holds the string which was expected, and the byte offset in the source where
+    /// it was expected. The text found there is given by [`TryParseError::found`].
+    ExpectedMismatch(&'static str, usize),
     /// The input string was given as invalid utf8.
     InvalidUtf8String,
+    /// The value doesn't fit in the fixed capacity of the type it's parsed into.
+    CapacityExceeded,
     /// Contains a generic error from `T`.
     Err(T),
 }
@@ -447,13 +1756,32 @@ pub enum TryParseError<T> {
 impl<T: core::fmt::Debug> core::fmt::Debug for TryParseError<T> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            Self::ExpectedMismatch(s, i) => write!(f, "expected: \"{s}\" but got: \"{i}\""),
+            Self::ExpectedMismatch(s, i) => write!(f, "expected: \"{s}\" at byte {i}"),
             Self::InvalidUtf8String => write!(f, "invalid utf8 encoding in source string"),
+            Self::CapacityExceeded => write!(f, "value exceeds the capacity of its type"),
             Self::Err(arg0) => arg0.fmt(f),
         }
     }
 }
 
+impl<T> TryParseError<T> {
+    /// Returns the text of `source` from where it stopped matching, if this is an
+    /// `ExpectedMismatch` of it.
+    /// ```
+    /// # use strp::{try_parse, TryParseError};
+    /// let source = "x = 10";
+    /// let err = try_parse!(source => "y = {}").map(|v: u32| v).unwrap_err();
+    /// assert_eq!(err, TryParseError::ExpectedMismatch("y = ", 0));
+    /// assert_eq!(err.found(source.as_bytes()), Some("x = 10".as_bytes()));
+    /// ```
+    pub fn found<'a>(&self, source: &'a [u8]) -> Option<&'a [u8]> {
+        match self {
+            Self::ExpectedMismatch(_, pos) => source.get(*pos..),
+            _ => None,
+        }
+    }
+}
+
 impl<T> From<T> for TryParseError<T> {
     fn from(value: T) -> Self {
         Self::Err(value)
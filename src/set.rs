@@ -0,0 +1,189 @@
+//! Matching a source string against many matching strings given at runtime.
+//!
+//! A [`PatternSet`] compiles matching strings, written in the same syntax as in the
+//! macros, into a trie over the text each of them starts with. Matching a source
+//! string only attempts the matching strings whose leading text the source starts
+//! with, rather than every one of them in turn. Values aren't parsed, and are
+//! returned as text, so types given to values are ignored.
+//!
+//! ```
+//! # use strp::set::PatternSet;
+//! let set = PatternSet::new(["move {x} {y}", "say {}", "{(quit|exit)}"]).unwrap();
+//!
+//! let found = set.matches("move 1 2").next().unwrap();
+//! assert_eq!((found.index, found.captures), (0, vec!["1", "2"]));
+//!
+//! assert!(set.is_match("exit"));
+//! assert!(!set.is_match("jump"));
+//! ```
+
+use crate::__private::alloc::{string::String, vec::Vec};
+use strp_core::syntax::{self, Kind, Parsed, Part};
+
+/// An error in one of the matching strings given to a [`PatternSet`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternError {
+    /// The index of the matching string.
+    pub index: usize,
+    /// Why the matching string is invalid.
+    pub reason: String,
+}
+
+impl core::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid matching string {}: {}", self.index, self.reason)
+    }
+}
+
+/// A matching string which matched a source string.
+#[derive(Debug, PartialEq)]
+pub struct SetMatch<'a> {
+    /// The index of the matching string in the set.
+    pub index: usize,
+    /// The text of each value which isn't skipped, in order.
+    pub captures: Vec<&'a str>,
+}
+
+/// A node of the trie over the leading text of the matching strings.
+#[derive(Default)]
+struct Node {
+    /// The next byte of leading text, and the index of the node it leads to.
+    children: Vec<(u8, usize)>,
+    /// The matching strings whose leading text ends at this node.
+    patterns: Vec<usize>,
+}
+
+/// A set of matching strings, which a source string is matched against at once.
+pub struct PatternSet {
+    patterns: Vec<Parsed>,
+    nodes: Vec<Node>,
+}
+
+impl PatternSet {
+    /// Compiles a set of matching strings, returning the first invalid one if any.
+    pub fn new<I>(patterns: I) -> Result<Self, PatternError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut set = Self {
+            patterns: Vec::new(),
+            nodes: Vec::from([Node::default()]),
+        };
+        for (index, pattern) in patterns.into_iter().enumerate() {
+            let compiled =
+                compile(pattern.as_ref()).map_err(|reason| PatternError { index, reason })?;
+            let mut node = 0;
+            for b in leading(&compiled).bytes() {
+                let children = &set.nodes[node].children;
+                node = match children.binary_search_by_key(&b, |child| child.0) {
+                    Ok(i) => children[i].1,
+                    Err(i) => {
+                        let next = set.nodes.len();
+                        set.nodes[node].children.insert(i, (b, next));
+                        set.nodes.push(Node::default());
+                        next
+                    }
+                };
+            }
+            set.nodes[node].patterns.push(index);
+            set.patterns.push(compiled);
+        }
+        Ok(set)
+    }
+
+    /// The number of matching strings in the set.
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Whether the set contains no matching strings.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns an iterator over the matching strings which match `source`, in the
+    /// order they were given in.
+    pub fn matches<'s, 'a>(&'s self, source: &'a str) -> Matches<'s, 'a> {
+        let mut candidates = Vec::new();
+        let mut node = &self.nodes[0];
+        candidates.extend_from_slice(&node.patterns);
+        for b in source.bytes() {
+            match node.children.binary_search_by_key(&b, |child| child.0) {
+                Ok(i) => node = &self.nodes[node.children[i].1],
+                Err(_) => break,
+            }
+            candidates.extend_from_slice(&node.patterns);
+        }
+        candidates.sort_unstable();
+        Matches {
+            set: self,
+            source,
+            candidates: candidates.into_iter(),
+        }
+    }
+
+    /// Whether any matching string in the set matches `source`.
+    pub fn is_match(&self, source: &str) -> bool {
+        self.matches(source).next().is_some()
+    }
+}
+
+/// An iterator over the matching strings of a [`PatternSet`] which match a source
+/// string, created through [`PatternSet::matches`].
+pub struct Matches<'s, 'a> {
+    set: &'s PatternSet,
+    source: &'a str,
+    candidates: crate::__private::alloc::vec::IntoIter<usize>,
+}
+
+impl<'a> Iterator for Matches<'_, 'a> {
+    type Item = SetMatch<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let source = self.source;
+        self.candidates.find_map(|index| {
+            let captures = captures(&self.set.patterns[index], source)?;
+            Some(SetMatch { index, captures })
+        })
+    }
+}
+
+/// Parses a matching string, rejecting values which only the macros can parse.
+fn compile(pattern: &str) -> Result<Parsed, String> {
+    let parsed = syntax::parse(pattern).map_err(|err| err.0)?;
+    for (value, ..) in &parsed.values {
+        if value.default.is_some() {
+            return Err("values given at runtime can't have a default".into());
+        }
+        if let Kind::Nested(_) = value.kind {
+            return Err("values given at runtime can't be nested matching strings".into());
+        }
+    }
+    Ok(parsed)
+}
+
+/// The text every match of a matching string starts with.
+fn leading(pattern: &Parsed) -> &str {
+    match pattern.values.first() {
+        Some((_, segment, _)) => segment.leading_str(),
+        None => pattern.tail.leading_str(),
+    }
+}
+
+/// Matches the whole of `source` against a matching string, returning the text of
+/// each value which isn't skipped.
+fn captures<'a>(pattern: &Parsed, source: &'a str) -> Option<Vec<&'a str>> {
+    let values = pattern.values.iter();
+    let parts = Vec::from_iter(values.map(|(value, seg, delims)| Part::new(value, seg, delims)));
+    let mut spans = Vec::new();
+    let found = syntax::with_segments(&parts, &pattern.tail, |segments, tail| {
+        let found = strp_core::match_values(source.as_bytes(), segments, tail, true, |i, span| {
+            spans.truncate(i);
+            spans.push(span);
+            Ok::<_, ()>(())
+        });
+        found.is_ok()
+    });
+    found.then(|| spans.into_iter().map(|span| &source[span]).collect())
+}
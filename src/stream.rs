@@ -0,0 +1,212 @@
+//! Push-based parsing of input that arrives in chunks, such as bytes read from a
+//! serial port or a socket.
+//!
+//! A [`StreamParser`] is created through the `stream_parser` macro, and buffers fed
+//! bytes in a fixed-capacity buffer until a whole frame matching the pattern has been
+//! received. The buffer is stored inline in the parser, which is usable in no_std
+//! contexts.
+//!
+//! Every status other than `Incomplete` reports how many bytes of the fed slice
+//! were used, and the bytes after them should be fed again. This also lets the
+//! parser resync to the next frame after an error.
+//!
+//! ```
+//! # use strp::{stream_parser, stream::{Status, StreamParser}};
+//! let mut parser: StreamParser<(u32, u32), 32> = stream_parser!("move {} {}\n");
+//!
+//! assert_eq!(parser.feed(b"move 1"), Status::Incomplete);
+//! assert_eq!(parser.feed(b"0 20\nmove"), Status::Done((10, 20), 5));
+//! ```
+
+use crate::{
+    __private::{Lit, Segment},
+    TryParseError,
+};
+
+/// The state of a [`StreamParser`] after being fed bytes.
+#[derive(Debug, PartialEq)]
+pub enum Status<T> {
+    /// The buffered bytes match the pattern so far, but more bytes are needed
+    /// before a value can be parsed.
+    Incomplete,
+    /// A value was parsed, using the given number of bytes from the last fed slice.
+    /// Any bytes after these belong to the next frame, and should be fed again.
+    Done(T, usize),
+    /// The buffered bytes can never match the pattern, or a value failed to parse,
+    /// using the given number of bytes from the last fed slice.
+    ///
+    /// When a whole frame failed to parse, the bytes after it belong to the next
+    /// frame. When the bytes can never match, only the bytes up to where matching
+    /// failed are used, so that feeding the rest again finds the next frame. If
+    /// matching failed within bytes buffered before, the buffered bytes after that
+    /// point are kept and matched again along with the rest.
+    Error(TryParseError<()>, usize),
+    /// The buffer filled up before a whole frame was received, using the given
+    /// number of bytes from the last fed slice.
+    Overflow(usize),
+}
+
+/// A push-based parser which buffers up to `N` bytes of a single frame.
+///
+/// Created through the `stream_parser` macro. Once a frame has been parsed, or an
+/// error occured, the buffered bytes of the frame are discarded and the parser
+/// starts over.
+pub struct StreamParser<T, const N: usize> {
+    sparse_data: &'static [Segment<'static>],
+    tail: &'static Segment<'static>,
+    parse: fn(&[u8]) -> Result<T, TryParseError<()>>,
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<T, const N: usize> StreamParser<T, N> {
+    #[doc(hidden)]
+    pub const fn new(
+        sparse_data: &'static [Segment<'static>],
+        tail: &'static Segment<'static>,
+        parse: fn(&[u8]) -> Result<T, TryParseError<()>>,
+    ) -> Self {
+        Self {
+            sparse_data,
+            tail,
+            parse,
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Feeds bytes into the parser, parsing a value once a whole frame has been
+    /// received.
+    pub fn feed(&mut self, bytes: &[u8]) -> Status<T> {
+        let prev_len = self.len;
+        let count = bytes.len().min(N - self.len);
+        self.buf[self.len..self.len + count].copy_from_slice(&bytes[..count]);
+        self.len += count;
+        match self.frame_len() {
+            Ok(Some(len)) => match self.parse_frame(len) {
+                Ok(ok) => Status::Done(ok, len - prev_len),
+                Err(e) => Status::Error(e, len - prev_len),
+            },
+            Ok(None) if self.len == N => {
+                self.reset();
+                Status::Overflow(count)
+            }
+            Ok(None) => Status::Incomplete,
+            Err((e, pos)) => {
+                // The frame can't start anywhere before `pos + 1`, so the bytes
+                // following it may hold the start of the next frame. Buffered bytes
+                // following it are kept to be matched again, and at least one byte
+                // is dropped, so feeding the rest always makes progress.
+                let kept = prev_len.saturating_sub(pos + 1);
+                self.buf.copy_within(prev_len - kept..prev_len, 0);
+                self.len = kept;
+                Status::Error(e, (pos + 1).saturating_sub(prev_len))
+            }
+        }
+    }
+
+    /// Signals that no more bytes will be fed, parsing whatever is buffered as a
+    /// whole frame.
+    ///
+    /// Required for patterns ending in a matched value, since there's no way to
+    /// tell where such a frame ends until the input ends.
+    pub fn finish(&mut self) -> Status<T> {
+        if self.len == 0 {
+            return Status::Incomplete;
+        }
+        match self.parse_frame(self.len) {
+            Ok(ok) => Status::Done(ok, 0),
+            Err(e) => Status::Error(e, 0),
+        }
+    }
+
+    /// Returns the bytes buffered so far.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Discards all buffered bytes.
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    fn parse_frame(&mut self, len: usize) -> Result<T, TryParseError<()>> {
+        let res = (self.parse)(&self.buf[..len]);
+        self.reset();
+        res
+    }
+
+    /// Finds the length of the first frame in the buffer, or `None` if the buffer
+    /// doesn't contain a whole frame yet. Mismatches are returned along with the
+    /// position of the string which failed to match.
+    fn frame_len(&self) -> Result<Option<usize>, (TryParseError<()>, usize)> {
+        let buf = self.buffered();
+        let mut pos = 0;
+        for segment in self.sparse_data {
+            match match_partial(segment.lits, &buf[pos..]) {
+                Partial::Complete(len) => pos += len,
+                Partial::Incomplete => return Ok(None),
+                Partial::Mismatch => {
+                    return Err((TryParseError::ExpectedMismatch(segment.m_str, pos), pos))
+                }
+            }
+            let end = match segment.class {
+                Some(class) => buf[pos..].iter().position(|b| !class.contains(*b)),
+                None if segment.delims.is_empty() => return Ok(None),
+                None => buf[pos..].iter().position(|b| segment.delims.contains(b)),
+            };
+            match end {
+                Some(i) => pos += i,
+                None => return Ok(None),
+            }
+        }
+        match match_partial(self.tail.lits, &buf[pos..]) {
+            Partial::Complete(len) => Ok(Some(pos + len)),
+            Partial::Incomplete => Ok(None),
+            Partial::Mismatch => Err((TryParseError::ExpectedMismatch(self.tail.m_str, pos), pos)),
+        }
+    }
+}
+
+/// How much of a string has been received.
+enum Partial {
+    /// The string was matched using the given amount of bytes.
+    Complete(usize),
+    /// The bytes received so far match the string, but don't cover all of it.
+    Incomplete,
+    /// The bytes received so far can't match the string.
+    Mismatch,
+}
+
+/// Matches `lits` against the start of `buf`. Alternatives are tried in the same
+/// order as when parsing, waiting for more bytes as soon as one could still match.
+fn match_partial(lits: &[Lit], buf: &[u8]) -> Partial {
+    let Some((lit, rest)) = lits.split_first() else {
+        return Partial::Complete(0);
+    };
+    let attempt = |m_str: &str| {
+        let len = m_str.len().min(buf.len());
+        if m_str.as_bytes()[..len] != buf[..len] {
+            Partial::Mismatch
+        } else if len < m_str.len() {
+            Partial::Incomplete
+        } else {
+            match match_partial(rest, &buf[len..]) {
+                Partial::Complete(rest_len) => Partial::Complete(len + rest_len),
+                partial => partial,
+            }
+        }
+    };
+    let (alts, opt): (&[&str], _) = match lit {
+        Lit::Str(m_str) => (core::slice::from_ref(m_str), false),
+        Lit::Opt(m_str) => (core::slice::from_ref(m_str), true),
+        Lit::Alt(alts) => (alts, false),
+    };
+    for m_str in alts.iter().chain(opt.then_some(&"")) {
+        match attempt(m_str) {
+            Partial::Mismatch => continue,
+            partial => return partial,
+        }
+    }
+    Partial::Mismatch
+}
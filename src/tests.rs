@@ -1,6 +1,12 @@
+#![allow(clippy::redundant_pattern_matching)]
+
 extern crate std;
 
-use crate::{try_parse, try_scan};
+use crate::TryParseError;
+use crate::{
+    find_all, is_match, match_str, parse, parse_or, parse_or_default, scan, scan_or,
+    scan_or_default, try_find, try_parse, try_parse_prefix, try_scan, try_scan_prefix, unparse,
+};
 use std::string::{String, ToString};
 
 #[test]
@@ -375,3 +381,1594 @@ fn parse_multiple_special_inline_mixed() {
     assert_eq!(hex, Ok(0xFE));
     assert_eq!(bin, 0b11111);
 }
+
+#[test]
+fn stream() {
+    use crate::{
+        stream::{Status, StreamParser},
+        stream_parser, TryParseError,
+    };
+
+    // Test some general cases that should always pass.
+
+    let mut parser: StreamParser<u32, 16> = stream_parser!("{}\n");
+    assert_eq!(parser.feed(b"12"), Status::Incomplete);
+    assert_eq!(parser.feed(b"34\n"), Status::Done(1234, 3));
+    assert_eq!(parser.buffered(), b"");
+
+    let mut parser: StreamParser<(u32, String), 32> = stream_parser!("id: {}, name: {};");
+    assert_eq!(parser.feed(b"id"), Status::Incomplete);
+    assert_eq!(parser.feed(b": 20, na"), Status::Incomplete);
+    assert_eq!(
+        parser.feed(b"me: hello;id: 30"),
+        Status::Done((20, "hello".to_string()), 10)
+    );
+    assert_eq!(
+        parser.feed(b"id: 30, name: world;"),
+        Status::Done((30, "world".to_string()), 20)
+    );
+
+    // Feeds several frames from a single chunk.
+    let mut parser: StreamParser<(u8, u8), 8> = stream_parser!("{},{};");
+    let mut chunk: &[u8] = b"1,2;3,4;5,";
+    let mut values = std::vec::Vec::new();
+    loop {
+        match parser.feed(chunk) {
+            Status::Done(v, consumed) => {
+                values.push(v);
+                chunk = &chunk[consumed..];
+            }
+            status => {
+                assert_eq!(status, Status::Incomplete);
+                break;
+            }
+        }
+    }
+    assert_eq!(values, [(1, 2), (3, 4)]);
+    assert_eq!(parser.buffered(), b"5,");
+    assert_eq!(parser.feed(b"6;"), Status::Done((5, 6), 2));
+
+    // Patterns ending in a value are only parsed once the input has finished.
+    let mut parser: StreamParser<u32, 8> = stream_parser!("n={}");
+    assert_eq!(parser.feed(b"n=10"), Status::Incomplete);
+    assert_eq!(parser.feed(b"20"), Status::Incomplete);
+    assert_eq!(parser.finish(), Status::Done(1020, 0));
+    assert_eq!(parser.finish(), Status::Incomplete);
+
+    // Test some special cases.
+
+    // Assures a mismatch is reported as soon as it's received, rather than
+    // waiting for more bytes.
+    let mut parser: StreamParser<u32, 16> = stream_parser!("value: {}\n");
+    assert_eq!(parser.feed(b"val"), Status::Incomplete);
+    assert_eq!(
+        parser.feed(b"ue; 10\n"),
+        Status::Error(TryParseError::ExpectedMismatch("value: ", 0), 0)
+    );
+    assert_eq!(parser.buffered(), b"al");
+    parser.reset();
+
+    // Assures an error when a whole frame was received but failed to parse,
+    // using only the bytes of that frame.
+    assert_eq!(
+        parser.feed(b"value: ten\nvalue: 1\n"),
+        Status::Error(TryParseError::Err(()), 11)
+    );
+    assert_eq!(parser.feed(b"value: 1\n"), Status::Done(1, 9));
+
+    // Assures an error when the trailing string doesn't match.
+    let mut parser: StreamParser<u32, 16> = stream_parser!("{};\n");
+    assert!(matches!(parser.feed(b"10;;"), Status::Error(_, 3)));
+
+    // Assures an overflow when the frame doesn't fit in the buffer, using only
+    // the bytes which were buffered.
+    let mut parser: StreamParser<u32, 4> = stream_parser!("{}\n");
+    assert_eq!(parser.feed(b"123"), Status::Incomplete);
+    assert_eq!(parser.feed(b"45\n"), Status::Overflow(1));
+    assert_eq!(parser.feed(b"1\n"), Status::Done(1, 2));
+
+    // Assures feeding the unused bytes after an error finds the next frame.
+    let mut parser: StreamParser<(u8, u8), 8> = stream_parser!("({},{})");
+    let mut chunk: &[u8] = b"x(1,2)y(4,5)";
+    let (mut values, mut errors) = (std::vec::Vec::new(), 0);
+    while !chunk.is_empty() {
+        match parser.feed(chunk) {
+            Status::Done(v, consumed) => {
+                values.push(v);
+                chunk = &chunk[consumed..];
+            }
+            Status::Error(_, consumed) => {
+                errors += 1;
+                chunk = &chunk[consumed..];
+            }
+            status => panic!("unexpected status: {status:?}"),
+        }
+    }
+    assert_eq!((values, errors), (std::vec![(1, 2), (4, 5)], 2));
+
+    // Assures a frame starting inside buffered bytes which failed to match is
+    // still found when the mismatch is split across two chunks.
+    let mut parser: StreamParser<u8, 8> = stream_parser!("aab{};");
+    assert_eq!(parser.feed(b"aa"), Status::Incomplete);
+    assert!(matches!(parser.feed(b"ab1;"), Status::Error(_, 0)));
+    assert_eq!(parser.buffered(), b"a");
+    assert_eq!(parser.feed(b"ab1;"), Status::Done(1, 4));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn prompt() {
+    use crate::__private::prompt;
+    use std::io::{self, BufReader, Read};
+    use std::vec::Vec;
+
+    fn ask(input: &[u8], limit: Option<usize>) -> (io::Result<Option<u16>>, String, usize) {
+        let (mut output, mut errors) = (Vec::new(), Vec::new());
+        let port = prompt(
+            &mut { input },
+            &mut output,
+            &mut errors,
+            &"port: ",
+            limit,
+            |s| try_parse!(s => "{}"),
+        );
+        let output = String::from_utf8(output).unwrap();
+        (port, output, errors.iter().filter(|&&b| b == b'\n').count())
+    }
+
+    // Test some general cases that should always pass.
+
+    let (port, output, errors) = ask(b"8080\n", None);
+    assert_eq!(
+        (port.unwrap(), output.as_str(), errors),
+        (Some(8080), "port: ", 0)
+    );
+
+    let (port, output, errors) = ask(b"http\n 8080 \n", None);
+    assert_eq!(
+        (port.unwrap(), output.as_str(), errors),
+        (Some(8080), "port: port: ", 1)
+    );
+
+    let (port, _, errors) = ask(b"a\nb\n8080\n", Some(3));
+    assert_eq!((port.unwrap(), errors), (Some(8080), 2));
+
+    // Test some special cases.
+
+    // Assures `None` once the input reaches EOF.
+    let (port, output, errors) = ask(b"", None);
+    assert_eq!(
+        (port.unwrap(), output.as_str(), errors),
+        (None, "port: ", 0)
+    );
+
+    let (port, output, errors) = ask(b"http\n", None);
+    assert_eq!(
+        (port.unwrap(), output.as_str(), errors),
+        (None, "port: port: ", 1)
+    );
+
+    // Assures `None` once the attempt limit is reached, without reading further.
+    let (port, output, errors) = ask(b"a\nb\n8080\n", Some(2));
+    assert_eq!(
+        (port.unwrap(), output.as_str(), errors),
+        (None, "port: port: ", 2)
+    );
+
+    // Assures I/O errors are returned rather than treated as EOF.
+    struct Failing;
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disconnected"))
+        }
+    }
+    let port: io::Result<Option<u16>> = prompt(
+        &mut BufReader::new(Failing),
+        &mut io::sink(),
+        &mut io::sink(),
+        &"port: ",
+        None,
+        |s| try_parse!(s => "{}"),
+    );
+    assert_eq!(port.unwrap_err().to_string(), "disconnected");
+
+    // Assures input which isn't valid UTF-8 is reported as an error.
+    let (port, _, _) = ask(b"80\xff\n", None);
+    assert_eq!(port.unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn parse_prefix() {
+    // Test some general cases that should always pass.
+
+    let v = try_parse_prefix!("20 30" => "{} ");
+    assert_eq!(v, Ok((20, "30")));
+
+    let v = try_parse_prefix!("number: 30, rest" => "number: {},");
+    assert_eq!(v, Ok((30, " rest")));
+
+    let v = try_parse_prefix!("hello world!" => "hello {}");
+    assert_eq!(v, Ok(("world!".to_string(), "")));
+
+    let source = "key=value; more".to_string();
+    let v = try_parse_prefix!(source => "key={};");
+    assert_eq!(v, Ok(("value".to_string(), " more")));
+
+    let source = std::vec![b'1', b',', b'2'];
+    let v = try_parse_prefix!(source => "{},");
+    assert_eq!(v, Ok((1, &b"2"[..])));
+
+    let v = try_scan_prefix!("10, 20; 30" => "{}, {};");
+    assert_eq!(v, Ok(((10, 20), " 30")));
+
+    let v = try_scan_prefix!("0b11 0xF rest" => "0b{:b} 0x{:x} ");
+    assert_eq!(v, Ok(((0b11, 0xF), "rest")));
+
+    // Chains multiple matching strings over one source.
+    let source = "x=10;y=20;z=30;";
+    let (x, rest) = try_parse_prefix!(source => "x={};").unwrap();
+    let (y, rest) = try_parse_prefix!(rest => "y={};").unwrap();
+    let (z, rest) = try_parse_prefix!(rest => "z={};").unwrap();
+    assert_eq!((x, y, z, rest), (10, 20, 30, ""));
+
+    // Returns only the rest when every value is inlined.
+    let mut v = -1;
+    let rest = try_parse_prefix!("v=5;w=6" => "v={v};");
+    assert_eq!((v, rest), (5, Ok("w=6")));
+
+    let (mut x, mut y) = (-1, -1);
+    let rest = try_scan_prefix!("1,2;3" => "{x},{y};");
+    assert_eq!((x, y, rest), (1, 2, Ok("3")));
+
+    let mut x = -1;
+    let v = try_scan_prefix!("1,2;3" => "{x},{};");
+    assert_eq!((x, v), (1, Ok((2, "3"))));
+
+    // Test some special cases.
+
+    // Assures an error when the trailing string doesn't match the start of the rest.
+    let v: Result<(u32, _), _> = try_parse_prefix!("20 30" => "{}, ");
+    assert!(matches!(v, Err(_)));
+
+    // Assures an error when the source is too short for the trailing string.
+    let v: Result<(u32, _), _> = try_parse_prefix!("20;" => "{};;");
+    assert!(matches!(v, Err(_)));
+
+    // Assures an error when the leading string doesn't match.
+    let v: Result<((u32, u32), _), _> = try_scan_prefix!("a 1 2" => "b {} {}");
+    assert!(matches!(v, Err(_)));
+
+    // Assures an error when a value fails to parse.
+    let v: Result<((u32, u32), _), _> = try_scan_prefix!("1 x;" => "{} {};");
+    assert!(matches!(v, Err(_)));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn find() {
+    // Test some general cases that should always pass.
+
+    let v = try_find!("took 20ms" => "took {}ms");
+    assert_eq!(v, Some((20, 0..9)));
+
+    let v = try_find!("request 5 took 20ms in total" => "took {}ms");
+    assert_eq!(v, Some((20, 10..19)));
+
+    let v = try_find!("a: 1, b: 2" => "b: {}");
+    assert_eq!(v, Some((2, 6..10)));
+
+    let v = try_find!("noise (10, 20) noise" => "({}, {})");
+    assert_eq!(v, Some(((10, 20), 6..14)));
+
+    let v = try_find!("id=0xFF;" => "id=0x{:x};");
+    assert_eq!(v, Some((0xFF, 0..8)));
+
+    let mut x = -1;
+    let v = try_find!("... x=5; ..." => "x={x};");
+    assert_eq!((x, v), (5, Some(4..8)));
+
+    let v: std::vec::Vec<(u32, _)> = find_all!("1; 2; x; 3;" => "{};").collect();
+    assert_eq!(v, [(1, 0..2), (2, 3..5), (3, 9..11)]);
+
+    let v: std::vec::Vec<((u32, u32), _)> = find_all!("[1,2] [3,4] [5,]" => "[{},{}]").collect();
+    assert_eq!(v, [((1, 2), 0..5), ((3, 4), 6..11)]);
+
+    let source = "k=1 k=2 k=3".to_string();
+    let v: std::vec::Vec<(u32, _)> = find_all!(source => "k={} ").collect();
+    assert_eq!(v, [(1, 0..4), (2, 4..8)]);
+
+    // Test some special cases.
+
+    // Assures the first match is returned when several positions match.
+    let v = try_find!("t=1 t=2" => "t={} ");
+    assert_eq!(v, Some((1, 0..4)));
+
+    // Assures matching continues past a position which matches the leading
+    // string, but fails to parse.
+    let v = try_find!("took xms, took 3ms" => "took {}ms");
+    assert_eq!(v, Some((3, 10..18)));
+
+    // Assures `None` when no position matches.
+    let v: Option<(u32, _)> = try_find!("nothing to see" => "took {}ms");
+    assert_eq!(v, None);
+
+    // Assures `None` when the source is empty.
+    let v: Option<(u32, _)> = try_find!("" => "took {}ms");
+    assert_eq!(v, None);
+
+    // Assures inlined values are left untouched when no position matches.
+    let mut x = -1;
+    let v = try_find!("x=a;" => "x={x};");
+    assert_eq!((x, v), (-1, None));
+
+    // Assures matches never overlap.
+    let v: std::vec::Vec<(String, _)> = find_all!("aaaa" => "a{}a").collect();
+    assert_eq!(v, [("".to_string(), 0..2), ("".to_string(), 2..4)]);
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn groups() {
+    use crate::{
+        stream::{Status, StreamParser},
+        stream_parser, TryParseError,
+    };
+
+    // Test some general cases that should always pass.
+
+    let v = try_parse!("took 20ms" => "took {}{[ ]}ms");
+    assert_eq!(v, Ok(20));
+
+    let v = try_parse!("took 20 ms" => "took {}{[ ]}ms");
+    assert_eq!(v, Ok(20));
+
+    let v = try_parse!("took5" => "took{[ ]}{}");
+    assert_eq!(v, Ok(5));
+
+    let v = try_parse!("took 5" => "took{[ ]}{}");
+    assert_eq!(v, Ok(5));
+
+    let v = try_parse!("ok: 10" => "{(ok|OK)}: {}");
+    assert_eq!(v, Ok(10));
+
+    let v = try_parse!("OK: 10" => "{(ok|OK)}: {}");
+    assert_eq!(v, Ok(10));
+
+    let v = try_parse!("10ms" => "{}{(ms|s)}");
+    assert_eq!(v, Ok(10));
+
+    let v = try_parse!("10s" => "{}{(ms|s)}");
+    assert_eq!(v, Ok(10));
+
+    let v = try_scan!("10 x 20" => "{}{[ ]}x{[ ]}{}");
+    assert_eq!(v, Ok((10, 20)));
+
+    let v = try_scan!("10x20" => "{}{[ ]}x{[ ]}{}");
+    assert_eq!(v, Ok((10, 20)));
+
+    let v = try_parse!("[a] b" => "[{}] {(a|b)}");
+    assert_eq!(v, Ok("a".to_string()));
+
+    let mut v = -1;
+    try_parse!("value = 5" => "value{[ ]}={[ ]}{v}");
+    assert_eq!(v, 5);
+
+    // Backtracks into the next alternative when the rest of the string fails to match.
+    let v = try_parse!("sec 5" => "{(s|sec)} {}");
+    assert_eq!(v, Ok(5));
+
+    let v = try_parse!("x=1;;" => "x={}{[;]};");
+    assert_eq!(v, Ok(1));
+
+    let v = try_parse_prefix!("took 5 ms, more" => "took {}{[ ]}ms");
+    assert_eq!(v, Ok((5, ", more")));
+
+    let v = try_find!("it took 5 secs" => "took {} {(ms|sec)}");
+    assert_eq!(v, Some((5, 3..13)));
+
+    let mut parser: StreamParser<u32, 16> = stream_parser!("{}{(\r\n|\n)}");
+    assert_eq!(parser.feed(b"10\r"), Status::Incomplete);
+    assert_eq!(parser.feed(b"\n"), Status::Done(10, 1));
+    assert_eq!(parser.feed(b"20\n30"), Status::Done(20, 3));
+
+    // Test some special cases.
+
+    // Assures an error when none of the alternatives match.
+    let v: Result<u32, _> = try_parse!("Ok: 10" => "{(ok|OK)}: {}");
+    assert_eq!(v, Err(TryParseError::ExpectedMismatch("{(ok|OK)}: ", 0)));
+
+    // Assures an error when the trailing alternatives don't match.
+    let v: Result<u32, _> = try_parse!("10m" => "{}{(ms|s)}");
+    assert!(matches!(v, Err(_)));
+
+    // Assures an error when text follows an optional group which would have
+    // to match the end.
+    let v: Result<u32, _> = try_parse!("10 x" => "{}{[ ]}");
+    assert!(matches!(v, Err(_)));
+
+    // Assures an error when a streamed frame can't match any alternative.
+    let mut parser: StreamParser<u32, 16> = stream_parser!("{(a|b)}={};");
+    assert!(matches!(parser.feed(b"c=1;"), Status::Error(_, 1)));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn classes() {
+    use crate::stream::{Status, StreamParser};
+    use crate::stream_parser;
+
+    // Test some general cases that should always pass.
+
+    let v = try_scan!("abc123def" => "{:alpha}{:digit}{}");
+    assert_eq!(v, Ok(("abc".to_string(), 123, "def".to_string())));
+
+    let v = try_scan!("key=value,rest" => "{:word}={:[^,]},{}");
+    assert_eq!(
+        v,
+        Ok(("key".to_string(), "value".to_string(), "rest".to_string()))
+    );
+
+    let v = try_parse!("2024-01-02" => "{:[0-9-]}");
+    assert_eq!(v, Ok("2024-01-02".to_string()));
+
+    let v = try_scan!("ab_1 cd" => "{:word}{:space}{}");
+    assert_eq!(
+        v,
+        Ok(("ab_1".to_string(), " ".to_string(), "cd".to_string()))
+    );
+
+    let v = try_parse!("a]b" => "a{:[\\]]}b");
+    assert_eq!(v, Ok("]".to_string()));
+
+    let (mut num, mut unit) = (0, String::new());
+    try_scan!("12kb" => "{num:digit}{unit:lower}").unwrap();
+    assert_eq!((num, unit), (12, "kb".to_string()));
+
+    let v = try_parse_prefix!("42abc" => "{:digit}");
+    assert_eq!(v, Ok((42, "abc")));
+
+    let v = try_find!("id: x12y" => "x{:digit}");
+    assert_eq!(v, Some((12, 4..7)));
+
+    let mut parser: StreamParser<(String, u32), 16> = stream_parser!("{:alpha}{:digit}");
+    assert_eq!(parser.feed(b"ab1"), Status::Incomplete);
+    assert_eq!(parser.feed(b"2;"), Status::Done(("ab".to_string(), 12), 1));
+
+    // Test some special cases.
+
+    // Assures an error when the class stops before the end of the source.
+    let v: Result<u32, _> = try_parse!("12ab" => "{:digit}");
+    assert!(matches!(v, Err(_)));
+
+    // Assures an error when the text following a class doesn't match.
+    let v: Result<(String, u32), _> = try_scan!("abc-12" => "{:alpha}:{}");
+    assert!(matches!(v, Err(_)));
+
+    // Assures a class may match nothing.
+    let v = try_scan!("123" => "{:alpha}{:digit}");
+    assert_eq!(v, Ok(("".to_string(), 123)));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn backtracking() {
+    // Test some general cases that should always pass.
+
+    let v = try_scan!("archive.tar.gz" => "{:+}.{}");
+    assert_eq!(v, Ok(("archive.tar".to_string(), "gz".to_string())));
+
+    let v = try_scan!("archive.tar.gz" => "{}.{}");
+    assert_eq!(v, Ok(("archive".to_string(), "tar.gz".to_string())));
+
+    let v = try_scan!("a.b.c" => "{:+}.{}");
+    assert_eq!(v, Ok(("a.b".to_string(), "c".to_string())));
+
+    let v = try_parse!("my.file.txt" => "{}.txt");
+    assert_eq!(v, Ok("my.file".to_string()));
+
+    let mut name = String::new();
+    try_parse!("notes.v2.md" => "{name:+}.md").unwrap();
+    assert_eq!(name, "notes.v2".to_string());
+
+    let (mut stem, mut ext) = (String::new(), String::new());
+    let dir: String = try_scan!("/usr/lib/libc.so.6" => "/{:+}/{stem:+}.{ext}").unwrap();
+    assert_eq!(dir, "usr/lib".to_string());
+    assert_eq!((stem, ext), ("libc.so".to_string(), "6".to_string()));
+
+    // Backtracks into a later delimiter when a value fails to parse.
+    let v = try_scan!("a.b.7" => "{}.{}");
+    assert_eq!(v, Ok(("a.b".to_string(), 7u32)));
+
+    let v = try_scan!("1,000,2" => "{},{}");
+    assert_eq!(v, Ok(("1,000".to_string(), 2u32)));
+
+    let v = try_scan!("0xff.txt" => "0x{:x}.{}");
+    assert_eq!(v, Ok((0xffu32, "txt".to_string())));
+
+    let v = try_parse_prefix!("a.b.c rest" => "{:+}.c ");
+    assert_eq!(v, Ok(("a.b".to_string(), "rest")));
+
+    let v = try_find!("file: a.b.c;" => " {:+}.{};");
+    assert_eq!(v, Some((("a.b".to_string(), "c".to_string()), 5..12)));
+
+    // Test some special cases.
+
+    // Assures an error when no split of the source allows every value to parse.
+    let v: Result<(u32, u32), _> = try_scan!("a.b.c" => "{}.{}");
+    assert!(matches!(v, Err(_)));
+
+    // Assures the error of the first attempted split is returned.
+    let v: Result<(u32, u32), _> = try_scan!("1.2" => "{}.{};");
+    assert_eq!(v, Err(crate::TryParseError::ExpectedMismatch(";", 3)));
+
+    // Assures failed splits aren't retried, keeping long mismatching sources fast.
+    let source = ",".repeat(2000);
+    let v: Result<(String, String, String, String), _> = try_scan!(source => "{},{},{},{};");
+    assert!(matches!(v, Err(_)));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn skip() {
+    use crate::stream::{Status, StreamParser};
+    use crate::stream_parser;
+
+    // Test some general cases that should always pass.
+
+    let v = try_parse!("GET /index.html HTTP/1.1" => "{_} {} {_}");
+    assert_eq!(v, Ok("/index.html".to_string()));
+
+    let v = try_parse!("id=7, name=x, age=20" => "id={_}, name={_}, age={}");
+    assert_eq!(v, Ok(20));
+
+    let v = try_scan!("10 skipped 20" => "{} {_} {}");
+    assert_eq!(v, Ok((10, 20)));
+
+    let v = try_scan!("a b c d" => "{} {_} {_} {}");
+    assert_eq!(v, Ok(("a".to_string(), "d".to_string())));
+
+    let v = try_parse!("abc123" => "{_:alpha}{}");
+    assert_eq!(v, Ok(123));
+
+    let v = try_parse!("a.b.c" => "{_:+}.{}");
+    assert_eq!(v, Ok("c".to_string()));
+
+    let mut x = 0;
+    try_parse!("skip 5" => "{_} {x}");
+    assert_eq!(x, 5);
+
+    let v = try_parse_prefix!("key: 5; rest" => "{_}: {};");
+    assert_eq!(v, Ok((5, " rest")));
+
+    let v = try_find!("[warn] took 25ms" => "] {_} {}ms");
+    assert_eq!(v, Some((25, 5..16)));
+
+    let mut parser: StreamParser<u32, 16> = stream_parser!("{_}={};");
+    assert_eq!(parser.feed(b"port=80;"), Status::Done(80, 8));
+
+    // Test some special cases.
+
+    // Assures a skipped value never affects parsing, no matter what it contains.
+    let v = try_parse!("\u{1F600}: 1" => "{_}: {}");
+    assert_eq!(v, Ok(1));
+
+    // Assures an error when the text around a skipped value doesn't match.
+    let v: Result<u32, _> = try_parse!("a;1" => "{_}: {}");
+    assert!(matches!(v, Err(_)));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn typed() {
+    // Test some general cases that should always pass.
+
+    let v = try_parse!("port: 8080" => "port: {:u16}");
+    assert_eq!(v, Ok(8080));
+
+    let v = try_scan!("1 2.5" => "{:u16} {:f64}");
+    assert_eq!(v, Ok((1, 2.5)));
+
+    let v = try_scan!("name: x" => "{:std::string::String}: {:String}");
+    assert_eq!(v, Ok(("name".to_string(), "x".to_string())));
+
+    let v = try_parse!("0xFFFFFFFFFF" => "0x{:x u64}");
+    assert_eq!(v, Ok(0xFFFFFFFFFF));
+
+    let v = try_parse!("0b1111" => "0b{:b u8}");
+    assert_eq!(v, Ok(0b1111));
+
+    let v = try_scan!("12kb" => "{:digit u32}{:alpha}");
+    assert_eq!(v, Ok((12, "kb".to_string())));
+
+    let v = try_scan!("ab12" => "{:[a-z] String}{:[0-9] i8}");
+    assert_eq!(v, Ok(("ab".to_string(), 12)));
+
+    let v = try_scan!("a.b.c" => "{:+ String}.{:String}");
+    assert_eq!(v, Ok(("a.b".to_string(), "c".to_string())));
+
+    let mut x = 0.0;
+    try_parse!("x = 1.5" => "x = {x:f64}");
+    assert_eq!(x, 1.5);
+
+    // Test some special cases.
+
+    // Assures an error when the value doesn't fit the given type.
+    let v = try_parse!("port: 80000" => "port: {:u16}");
+    assert!(matches!(v, Err(_)));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn places() {
+    #[derive(Default)]
+    struct Size {
+        w: u32,
+        h: u32,
+    }
+
+    #[derive(Default)]
+    struct Config {
+        size: Size,
+        name: String,
+    }
+
+    // Test some general cases that should always pass.
+
+    let mut size = Size::default();
+    try_scan!("20x30" => "{size.w}x{size.h}").unwrap();
+    assert_eq!((size.w, size.h), (20, 30));
+
+    let mut cfg = Config::default();
+    try_scan!("main: 1x2" => "{cfg.name}: {cfg.size.w}x{cfg.size.h}").unwrap();
+    assert_eq!(
+        (cfg.name, cfg.size.w, cfg.size.h),
+        ("main".to_string(), 1, 2)
+    );
+
+    let mut arr = [0; 3];
+    try_scan!("1 2 3" => "{arr[0]} {arr[1]} {arr[2]}").unwrap();
+    assert_eq!(arr, [1, 2, 3]);
+
+    let mut pair = (0, 0);
+    let v = try_scan!("4 5 6" => "{pair.0} {} {pair.1}");
+    assert_eq!((v, pair), (Ok(5), (4, 6)));
+
+    let mut x = 0;
+    let p = &mut x;
+    try_parse!("7" => "{*p}").unwrap();
+    assert_eq!(x, 7);
+
+    let mut size = Size::default();
+    try_parse!("w = 0xff" => "w = 0x{size.w:x}").unwrap();
+    assert_eq!(size.w, 0xff);
+
+    // Test some special cases.
+
+    // Assures places are left untouched when parsing fails.
+    let mut size = Size { w: 1, h: 2 };
+    let v = try_scan!("3xa" => "{size.w}x{size.h}");
+    assert!(matches!(v, Err(_)));
+    assert_eq!((size.w, size.h), (1, 2));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn args() {
+    #[derive(Default)]
+    struct Config {
+        a: u32,
+        b: String,
+    }
+
+    // Test some general cases that should always pass.
+
+    let (mut cfg, mut other) = (Config::default(), 0);
+    try_scan!("1 2" => "{a} {b}", a = &mut cfg.a, b = &mut other).unwrap();
+    assert_eq!((cfg.a, other), (1, 2));
+
+    let (mut x, mut y) = (0, 0);
+    try_scan!("3 4" => "{} {}", &mut x, &mut y).unwrap();
+    assert_eq!((x, y), (3, 4));
+
+    let mut x = 0;
+    try_parse!("5" => "{}", &mut x).unwrap();
+    assert_eq!(x, 5);
+
+    let mut cfg = Config::default();
+    let (a, b) = (&mut cfg.a, &mut cfg.b);
+    try_scan!("x=6 y=z" => "x={} y={}", a, b).unwrap();
+    assert_eq!((cfg.a, cfg.b), (6, "z".to_string()));
+
+    // Named arguments may be mixed with positional ones and local variables.
+    let (mut x, mut y, mut z) = (0, 0, 0);
+    try_scan!("7 8 9" => "{} {y} {n}", &mut x, n = &mut z).unwrap();
+    assert_eq!((x, y, z), (7, 8, 9));
+
+    let mut x = 0;
+    let v = try_scan!("1, 2" => "{n}, {}", n = &mut x);
+    assert_eq!((v, x), (Ok(2), 1));
+
+    let mut x = 0;
+    let v = try_parse!("0xff" => "0x{n:x}", n = &mut x);
+    assert_eq!((v, x), (Ok(()), 0xff));
+
+    let mut x = 0;
+    let rest = try_parse_prefix!("10; rest" => "{};", &mut x);
+    assert_eq!((rest, x), (Ok(" rest"), 10));
+
+    let mut x = 0;
+    let found = try_find!("a b=20 c" => "b={n} ", n = &mut x);
+    assert_eq!((found, x), (Some(2..7), 20));
+
+    // Test some special cases.
+
+    // Assures targets are left untouched when parsing fails.
+    let (mut x, mut y) = (1, 2);
+    let v = try_scan!("3 a" => "{} {}", &mut x, &mut y);
+    assert!(matches!(v, Err(_)));
+    assert_eq!((x, y), (1, 2));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn nested() {
+    // Test some general cases that should always pass.
+
+    let v = try_scan!("p=1,2 n=3" => r#"p={:(i32, i32) "{},{}"} n={}"#);
+    assert_eq!(v, Ok(((1, 2), 3)));
+
+    let v: Result<(u8, u8), _> = try_parse!("(4 5)" => r#"({:"{} {}"})"#);
+    assert_eq!(v, Ok((4, 5)));
+
+    let v = try_scan!("x=1 y=2 z=3, 4" => r#"{:"x={} y={_} z={:u32}"}, {:u8}"#);
+    assert_eq!(v, Ok(((1, 3), 4)));
+
+    let mut p = (String::new(), 0);
+    try_parse!("host:80" => r#"{p:(String, u16) "{}:{}"}"#);
+    assert_eq!(p, ("host".to_string(), 80));
+
+    // Nested strings may be nested again, escaping their quotes.
+    let v = try_scan!("a: 0x10/b; c" => r#"{:"{}: {:\"0x{:x u8}/{:[a-z]}\"}"}; {}"#);
+    assert_eq!(
+        v,
+        Ok((("a".to_string(), (16, "b".to_string())), "c".to_string()))
+    );
+
+    // Test some special cases.
+
+    // Assures quotes outside of values are still matched literally.
+    let v = try_scan!(r#""a" 1,2"# => r#""{}" {:(u8, u8) "{},{}"}"#);
+    assert_eq!(v, Ok(("a".to_string(), (1, 2))));
+
+    // Assures the nested string has to match all of the value.
+    let v = try_scan!("1,2,3 4" => r#"{:(u8, u8) "{},{}"} {:u8}"#);
+    assert!(matches!(v, Err(_)));
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn defaults() {
+    // Test some general cases that should always pass.
+
+    assert_eq!(parse_or!("port: 443" => "port: {}", 8080), 443);
+    assert_eq!(parse_or!("port: none" => "port: {}", 8080), 8080);
+    assert_eq!(parse_or!("p 1" => "port: {}", 8080), 8080);
+
+    let count: u32 = parse_or_default!("count: many" => "count: {}");
+    assert_eq!(count, 0);
+
+    assert_eq!(scan_or!("1x2" => "{}x{}", (0, 0)), (1, 2));
+    assert_eq!(scan_or!("1xy" => "{}x{}", (0, 0)), (0, 0));
+
+    let (w, h): (u32, u32) = scan_or_default!("20x?" => "{}x{}");
+    assert_eq!((w, h), (0, 0));
+
+    // Arguments may still be given before the default.
+    let mut x = 0;
+    assert_eq!(parse_or!("7" => "{}", &mut x, ()), ());
+    assert_eq!(x, 7);
+
+    // Values may have a default of their own.
+    let v = try_scan!("host:" => "{}:{=8080}");
+    assert_eq!(v, Ok(("host".to_string(), 8080)));
+
+    let v = try_scan!("host:http" => "{}:{=8080}");
+    assert_eq!(v, Ok(("host".to_string(), 8080)));
+
+    let v = try_scan!("host:80" => "{}:{:u16=8080}");
+    assert_eq!(v, Ok(("host".to_string(), 80)));
+
+    let mut port = 0;
+    try_parse!("port=" => "port={port=8080}").unwrap();
+    assert_eq!(port, 8080);
+
+    let v = try_scan!("a=, b=2" => r#"a={=String::from("{none}")}, b={:u8}"#);
+    assert_eq!(v, Ok(("{none}".to_string(), 2)));
+
+    let v = try_scan!("0x, 1" => "0x{:x u32=0xff}, {:u8}");
+    assert_eq!(v, Ok((0xff, 1)));
+
+    // Test some special cases.
+
+    // Assures defaults don't hide mismatches of the rest of the string.
+    let v: Result<(String, u16), _> = try_scan!("host;80" => "{}:{=8080}");
+    assert!(matches!(v, Err(_)));
+
+    // Assures nested strings fall back to their default as a whole.
+    let v = try_parse!("1,x" => r#"{:(u8, u8) "{},{}"=(0, 0)}"#);
+    assert_eq!(v, Ok((0, 0)));
+}
+
+#[test]
+fn panics() {
+    fn message(f: impl FnOnce() + std::panic::UnwindSafe) -> String {
+        let payload = std::panic::catch_unwind(f).expect_err("expected a panic");
+        payload
+            .downcast::<String>()
+            .map(|message| *message)
+            .expect("expected a formatted panic message")
+    }
+
+    // Test some general cases that should always pass.
+
+    let m = message(|| {
+        let _: u32 = parse!("number: abc" => "number: {}");
+    });
+    assert_eq!(
+        m,
+        "failed to match \"number: abc\" with \"number: {}\" in value 1 (`{}`): \
+        ParseIntError { kind: InvalidDigit }"
+    );
+
+    let m = message(|| {
+        let source = "1 to 2";
+        let _: (u8, u8) = scan!(source => "{} - {:u8}");
+    });
+    assert_eq!(
+        m,
+        "failed to match \"1 to 2\" with \"{} - {:u8}\" in the text before value 2 (`{:u8}`): \
+        expected: \" - \" at byte 1, found \" to 2\""
+    );
+
+    let m = message(|| {
+        let source = "1 - 2";
+        let _: (u8, u8) = scan!(source => "{} - {}.");
+    });
+    assert_eq!(
+        m,
+        "failed to match \"1 - 2\" with \"{} - {}.\" in the trailing text: \
+        expected: \".\" at byte 5, found \"\""
+    );
+
+    // Test some special cases.
+
+    // Assures long inputs are truncated.
+    let m = message(|| {
+        let source = "0123456789".repeat(10);
+        let _: u8 = parse!(source => "{}");
+    });
+    assert!(m.starts_with(&std::format!(
+        "failed to match \"{}...\"",
+        &"0123456789".repeat(7)[..64]
+    )));
+}
+
+#[test]
+fn unparse() {
+    #[derive(Default)]
+    struct Size {
+        w: u32,
+        h: u32,
+    }
+
+    // Test some general cases that should always pass.
+
+    let (a, b) = (10, 255);
+    assert_eq!(unparse!("{} -> {:x}", a, b), "10 -> ff");
+    assert_eq!(unparse!("{a}, {:b}", 5), "10, 101");
+    assert_eq!(unparse!("{n:u16} {{}}", n = 8080), "8080 {}");
+
+    let size = Size { w: 20, h: 30 };
+    let line = unparse!("{size.w}x{size.h}");
+    assert_eq!(line, "20x30");
+    let mut parsed = Size::default();
+    try_scan!(line => "{parsed.w}x{parsed.h}").unwrap();
+    assert_eq!((parsed.w, parsed.h), (20, 30));
+
+    let line = unparse!(r#"{}: {:"({}, {:x})"}"#, "p", (1, -2));
+    assert_eq!(line, "p: (1, -2)");
+    assert_eq!(
+        try_scan!(line => r#"{}: {:"({}, {:x})"}"#),
+        Ok(("p".to_string(), (1, -2)))
+    );
+
+    // Optional text is rendered, and alternatives are rendered as the first one.
+    assert_eq!(unparse!("{}{[ ms]}, {(on|off)}", 5), "5 ms, on");
+
+    // Test some special cases.
+
+    // Assures negative values are rendered with a sign in hexadecimal and binary.
+    assert_eq!(unparse!("{:x} {:b}", -255i16, -2i8), "-ff -10");
+    assert_eq!(unparse!("{:x}", i8::MIN), "-80");
+    assert_eq!(try_parse!(unparse!("{:x}", i8::MIN) => "{:x}"), Ok(i8::MIN));
+}
+
+proptest::proptest! {
+    #[test]
+    fn round_trip_ints(a: i64, b: u32, c: i8, d: u128) {
+        let line = unparse!("{} {:x}, {:b}; {:x u128}", a, b, c, d);
+        proptest::prop_assert_eq!(try_scan!(line => "{} {:x}, {:b}; {:x u128}"), Ok((a, b, c, d)));
+    }
+
+    #[test]
+    fn round_trip_floats(a: f64, b: f32) {
+        let line = unparse!("({}, {})", a, b);
+        let v: Result<(f64, f32), _> = try_scan!(line => "({}, {})");
+        let (x, y) = v.unwrap();
+        proptest::prop_assert!(x == a || (x.is_nan() && a.is_nan()));
+        proptest::prop_assert!(y == b || (y.is_nan() && b.is_nan()));
+    }
+
+    #[test]
+    fn round_trip_nested(a: i16, b: u8, c: isize) {
+        let line = unparse!(r#"{:"{}/{:x}"} -> {:b}"#, (a, b), c);
+        proptest::prop_assert_eq!(try_scan!(line => r#"{:"{}/{:x}"} -> {:b}"#), Ok(((a, b), c)));
+    }
+}
+
+/// Parses `bytes` as before numbers were parsed without allocating: decoding them
+/// first, then handing them to `from_str_radix`.
+fn decoded<T>(
+    bytes: &[u8],
+    radix: u32,
+    from_str_radix: fn(&str, u32) -> Result<T, core::num::ParseIntError>,
+) -> Result<T, TryParseError<core::num::ParseIntError>> {
+    let str = core::str::from_utf8(bytes).or(Err(TryParseError::InvalidUtf8String))?;
+    Ok(from_str_radix(str, radix)?)
+}
+
+proptest::proptest! {
+    #[test]
+    fn ints_match_std(text in "[-+]?[0-9a-fA-F_]{0,45}", bytes: std::vec::Vec<u8>) {
+        use crate::{__private::{Binary, Hex}, TryParse};
+
+        for bytes in [text.as_bytes(), &bytes] {
+            let iter = || bytes.iter().copied();
+            proptest::prop_assert_eq!(u8::try_parse(&mut iter()), decoded(bytes, 10, u8::from_str_radix));
+            proptest::prop_assert_eq!(i32::try_parse(&mut iter()), decoded(bytes, 10, i32::from_str_radix));
+            proptest::prop_assert_eq!(i128::try_parse(&mut iter()), decoded(bytes, 10, i128::from_str_radix));
+            proptest::prop_assert_eq!(
+                Hex::<i8>::try_parse(&mut iter()).map(Hex::into_inner),
+                decoded(bytes, 16, i8::from_str_radix)
+            );
+            proptest::prop_assert_eq!(
+                Binary::<u64>::try_parse(&mut iter()).map(Binary::into_inner),
+                decoded(bytes, 2, u64::from_str_radix)
+            );
+        }
+    }
+}
+
+/// Parses `bytes` as before floats were parsed without collecting them: decoding
+/// them first, then handing them to `str::parse`.
+fn decoded_float<T: core::str::FromStr>(bytes: &[u8]) -> Result<T, TryParseError<T::Err>> {
+    let str = core::str::from_utf8(bytes).or(Err(TryParseError::InvalidUtf8String))?;
+    Ok(str.parse()?)
+}
+
+proptest::proptest! {
+    #[test]
+    fn floats_match_std(
+        text in "[-+]?([0-9]{0,30}[.]?[0-9]{0,30}([eE][-+]?[0-9]{0,12})?|(?i:inf|infinity|nan)x?)",
+        long in "0{0,20}[0-9]{700,900}[.]?[0-9]{0,100}0{0,20}[eE]-?[0-9]{1,3}",
+        bytes: std::vec::Vec<u8>,
+    ) {
+        use crate::TryParse;
+
+        for bytes in [text.as_bytes(), long.as_bytes(), &bytes] {
+            let iter = || bytes.iter().copied();
+            proptest::prop_assert_eq!(
+                f64::try_parse(&mut iter()).map(f64::to_bits),
+                decoded_float(bytes).map(f64::to_bits)
+            );
+            proptest::prop_assert_eq!(
+                f32::try_parse(&mut iter()).map(f32::to_bits),
+                decoded_float(bytes).map(f32::to_bits)
+            );
+        }
+    }
+}
+
+#[test]
+fn numbers() {
+    use crate::TryParse;
+    use core::num::IntErrorKind;
+
+    let kind = |result: Result<i8, TryParseError<core::num::ParseIntError>>| match result {
+        Err(TryParseError::Err(err)) => Some(*err.kind()),
+        _ => None,
+    };
+
+    // Test some general cases that should always pass.
+
+    assert_eq!(try_parse!("-128" => "{}"), Ok(i8::MIN));
+    assert_eq!(try_parse!("+255" => "{}"), Ok(u8::MAX));
+    assert_eq!(try_parse!("-7f" => "{:x}"), Ok(-127i8));
+    assert_eq!(try_parse!("2.5e3" => "{}"), Ok(2500f32));
+    assert_eq!(try_parse!("[-1, 0.25]" => "[{}, {}]"), Ok((-1i64, 0.25f64)));
+
+    // Test some special cases.
+
+    // Assures overflow and invalid digits are reported as by `from_str_radix`.
+    let (positive, negative, sign, space, empty) = ("128", "-129", "-", "1 ", "");
+    assert_eq!(
+        kind(try_parse!(positive => "{}")),
+        Some(IntErrorKind::PosOverflow)
+    );
+    assert_eq!(
+        kind(try_parse!(negative => "{}")),
+        Some(IntErrorKind::NegOverflow)
+    );
+    assert_eq!(
+        kind(try_parse!(sign => "{}")),
+        Some(IntErrorKind::InvalidDigit)
+    );
+    assert_eq!(
+        kind(try_parse!(space => "{}")),
+        Some(IntErrorKind::InvalidDigit)
+    );
+    assert_eq!(kind(try_parse!(empty => "{}")), Some(IntErrorKind::Empty));
+    assert_eq!(
+        u16::try_parse(&mut "-1".bytes()),
+        Err(TryParseError::Err("-1".parse::<u16>().unwrap_err()))
+    );
+    // Assures invalid utf8 is reported before any other error.
+    assert_eq!(
+        i8::try_parse(&mut b"999\xff".iter().copied()),
+        Err(TryParseError::InvalidUtf8String)
+    );
+    assert_eq!(
+        i8::try_parse(&mut b"1x\xe2\x82".iter().copied()),
+        Err(TryParseError::InvalidUtf8String)
+    );
+    assert!(matches!(
+        i8::try_parse(&mut "1\u{20ac}".bytes()),
+        Err(TryParseError::Err(_))
+    ));
+    // Assures floats longer than the buffer on the stack are still parsed.
+    let long = std::format!("0.{}1", "0".repeat(100));
+    assert_eq!(f64::try_parse(&mut long.bytes()), Ok(long.parse().unwrap()));
+    // Assures digits past the ones kept still decide how a float is rounded.
+    let long = std::format!("9007199254740993.{}1", "0".repeat(1000));
+    assert_eq!(f64::try_parse(&mut long.bytes()), Ok(9007199254740994.0));
+    let long = std::format!("9007199254740993.{}", "0".repeat(1000));
+    assert_eq!(f64::try_parse(&mut long.bytes()), Ok(9007199254740992.0));
+    let long = std::format!("{}e-1000", "1".repeat(1000));
+    assert_eq!(f64::try_parse(&mut long.bytes()), Ok(long.parse().unwrap()));
+    assert_eq!(
+        f64::try_parse(&mut b"1.5\xff".iter().copied()),
+        Err(TryParseError::InvalidUtf8String)
+    );
+}
+
+#[allow(unused_must_use)]
+#[test]
+fn arities() {
+    // Test some general cases that should always pass.
+
+    assert_eq!(try_parse!("ready" => "ready"), Ok(()));
+    assert_eq!(try_scan!("ready" => "ready"), Ok(()));
+    assert!(matches!(try_parse!("ready?" => "ready"), Err(_)));
+    parse!("a, b" => "{_}, {_}");
+
+    assert_eq!(try_scan!("20" => "{}"), Ok(20));
+    assert_eq!(try_scan!("x: 0xff" => "x: 0x{:x}"), Ok(255));
+
+    let v = try_parse!("1 2 3" => "{} {} {}");
+    assert_eq!(v, Ok((1, 2, 3)));
+
+    let (w, h): (u32, u32) = parse!("20x30" => "{}x{}");
+    assert_eq!((w, h), (20, 30));
+
+    let rest = try_scan_prefix!("ab" => "a");
+    assert_eq!(rest, Ok("b"));
+
+    let (mut x, mut y) = (0, 0);
+    let v = try_parse!("1 2 3" => "{x} {} {y}");
+    assert_eq!((v, x, y), (Ok(2), 1, 3));
+
+    assert!(is_match!("GET /index.html" => "GET {}"));
+    assert!(is_match!("port: 80" => "port: {:u16}"));
+    assert!(!is_match!("port: 80000" => "port: {:u16}"));
+    assert!(is_match!("a 1,2" => r#"{} {:(u8, u8) "{},{}"}"#));
+    assert!(!is_match!("GET" => "POST {}"));
+
+    // Test some special cases.
+
+    // Assures values without a type are matched without being parsed in `is_match`,
+    // except for checking the digits of hexadecimal and binary values.
+    assert!(is_match!("id: zz" => "id: {}"));
+    assert!(!is_match!("zz" => "{:x}"));
+    assert!(!is_match!("2" => "{:b}"));
+    assert!(!is_match!("0xzz" => "0x{:x u8}"));
+    assert!(is_match!("0xff, -101" => "0x{:x}, {:b}"));
+    assert!(is_match!("0xffffffffffffffffffffffffffffffffff" => "0x{:x}"));
+
+    assert_eq!(try_find!("a b c" => "b"), Some(2..3));
+
+    // Assures more values than fit in a single tuple can be matched, including mixed types.
+    let v = try_parse!(
+        "1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 -17 x" =>
+        "{:u8} {:u8} {:u8} {:u8} {:u8} {:u8} {:u8} {:u8} {:u8} {:u8} {:u8} {:u8} {:u8} {:u8} \
+        {:u8} {:u16} {:i32} {:String}"
+    );
+    let (a, .., p, q, r) = v.unwrap();
+    assert_eq!((a, p, q, r.as_str()), (1, 16, -17, "x"));
+    let v = match_str!("0 1 2 3 4 5 6 7 8 9 a b c d e f 10 x" {
+        "{a:x u8} {b:x u8} {c:x u8} {d:x u8} {e:x u8} {f:x u8} {g:x u8} {h:x u8} \
+        {i:x u8} {j:x u8} {k:x u8} {l:x u8} {m:x u8} {n:x u8} {o:x u8} {p:x u8} \
+        {q:x u16} {r:String}" => (a, b + p, q, r),
+        _ => (0, 0, 0, String::new()),
+    });
+    assert_eq!(v, (0, 16, 16, "x".to_string()));
+    assert!(is_match!(
+        "(1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,x)" =>
+        r#"{:(u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,String) "({},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{})"}"#
+    ));
+}
+
+#[test]
+fn match_str() {
+    #[derive(Debug, PartialEq)]
+    enum Command {
+        Move(i32, i32),
+        Say(String),
+        Color(u8, u8, u8),
+        Quit,
+        Unknown,
+    }
+
+    fn command(line: &str) -> Command {
+        match_str!(line {
+            "move {x} {y}" => Command::Move(x, y),
+            "move {x}" => Command::Move(x, 0),
+            "say {msg}" => Command::Say(msg),
+            "color #{r:x u8},{g:x},{b:x}" => Command::Color(r, g, b),
+            "{(quit|exit)}" => Command::Quit,
+            "q" => Command::Quit,
+            _ => Command::Unknown,
+        })
+    }
+
+    // Test some general cases that should always pass.
+
+    assert_eq!(command("move 1 -2"), Command::Move(1, -2));
+    assert_eq!(command("move 3"), Command::Move(3, 0));
+    assert_eq!(command("say hello"), Command::Say("hello".to_string()));
+    assert_eq!(command("color #ff,80,0"), Command::Color(255, 128, 0));
+    assert_eq!(command("quit"), Command::Quit);
+    assert_eq!(command("exit"), Command::Quit);
+    assert_eq!(command("q"), Command::Quit);
+    assert_eq!(command("jump"), Command::Unknown);
+    assert_eq!(command("move a"), Command::Unknown);
+    assert_eq!(command("color #ff,80"), Command::Unknown);
+
+    // Arms may skip values, and evaluate to blocks.
+    let v = match_str!("a=1, b=2" {
+        "a={_}, b={b:u8}" => {
+            let doubled = b * 2;
+            doubled + 1
+        }
+        _ => 0,
+    });
+    assert_eq!(v, 5);
+
+    // Test some special cases.
+
+    // Assures arms are attempted in order, even when they don't share a prefix.
+    let v = match_str!(b"abc".as_slice() {
+        "{_}c" => 1,
+        "ab{_}" => 2,
+        _ => 0,
+    });
+    assert_eq!(v, 1);
+
+    // Assures arms sharing a prefix fall through to the following arms.
+    let v = match_str!("move" {
+        "move {_}" => 1,
+        "mo{_}" => 2,
+        "move" => 3,
+        _ => 0,
+    });
+    assert_eq!(v, 2);
+
+    // Assures arms sharing leading text with an arm which isn't next to them are
+    // matched past it, in order.
+    let arm = |line: &str| {
+        match_str!(line {
+            "set {_} on" => 1,
+            "get {n:u8}" => 2 + n,
+            "set {n:u8}" => 10 + n,
+            "{(s|g)}et" => 20,
+            _ => 0,
+        })
+    };
+    assert_eq!(
+        (
+            arm("set a on"),
+            arm("get 1"),
+            arm("set 2"),
+            arm("set"),
+            arm("sets")
+        ),
+        (1, 3, 12, 20, 0)
+    );
+
+    // Assures control flow in arms applies to the surrounding code.
+    let f = |line: &str| -> Option<u32> {
+        let n = match_str!(line {
+            "{n}" => n,
+            _ => return None,
+        });
+        Some(n)
+    };
+    assert_eq!((f("5"), f("x")), (Some(5), None));
+}
+
+#[test]
+fn pattern_set() {
+    use crate::set::{PatternError, PatternSet, SetMatch};
+    use std::{vec, vec::Vec};
+
+    let set = PatternSet::new([
+        "move {x} {y}",
+        "move {x}",
+        "say {msg}",
+        "color #{r:[0-9a-f]}/{g:x}/{_}",
+        "{(quit|exit)}",
+        "{[un]}mute {:+}",
+        "{_} dies",
+    ])
+    .unwrap();
+    let indices = |source| Vec::from_iter(set.matches(source).map(|m| m.index));
+
+    // Test some general cases that should always pass.
+
+    assert_eq!(set.len(), 7);
+    assert_eq!(
+        set.matches("move 1 -2").next(),
+        Some(SetMatch {
+            index: 0,
+            captures: vec!["1", "-2"]
+        })
+    );
+    assert_eq!(indices("move 3"), [1]);
+    assert_eq!(indices("say hello"), [2]);
+    assert_eq!(indices("exit"), [4]);
+    assert_eq!(indices("jump"), []);
+    assert_eq!(
+        set.matches("color #ff/a0/x").next().unwrap().captures,
+        ["ff", "a0"]
+    );
+    assert_eq!(set.matches("unmute a b").next().unwrap().captures, ["a b"]);
+    assert_eq!(indices("mute a"), [5]);
+    assert!(set.is_match("bob dies"));
+    assert!(!set.is_match("bob lives"));
+
+    // Test some special cases.
+
+    // Assures every matching string which matches is returned, in order.
+    assert_eq!(indices("say dies"), [2, 6]);
+    assert_eq!(indices("move 1 2 dies"), [0, 1, 6]);
+
+    // Assures values with a character class end at the first byte outside of it.
+    assert_eq!(indices("color #fg/0/0"), []);
+
+    // Assures an empty set matches nothing.
+    let empty = PatternSet::new::<[&str; 0]>([]).unwrap();
+    assert!(empty.is_empty() && !empty.is_match(""));
+
+    // Assures invalid matching strings are reported along with their index.
+    let error = |pattern| PatternSet::new(["{}", pattern]).err().map(|e| e.index);
+    assert_eq!(error("{}{[ ]}{}"), Some(1));
+    assert_eq!(error("{x=5}"), Some(1));
+    assert_eq!(error("{:\"{} {}\"}"), Some(1));
+    assert_eq!(error("{[]}"), Some(1));
+    assert_eq!(error("{(a|a)}"), Some(1));
+    assert_eq!(error("}"), Some(1));
+    assert_eq!(error("{:[z-a]}"), Some(1));
+    assert_eq!(error("{:digit}{}"), None);
+    assert_eq!(error("{}{}"), None);
+    assert_eq!(
+        PatternSet::new(["{"]).err(),
+        Some(PatternError {
+            index: 0,
+            reason: "missing token after '{'".to_string()
+        })
+    );
+
+    // Assures matching strings are rejected for the same reasons as in the macros.
+    let reason = |pattern| PatternSet::new([pattern]).err().unwrap().reason;
+    assert_eq!(
+        reason("{(a|ab)}{}"),
+        "ambiguous matching string: alternative \"a\" is a prefix of \"ab\", and is always \
+        used since a value follows. Try listing \"ab\" first"
+    );
+    assert_eq!(reason("{:[z-a]}"), "invalid range 'z-a' in character class");
+}
+
+#[test]
+fn parser() {
+    use crate::{parser, TryParseError};
+    use core::num::ParseIntError;
+
+    #[parser("{} -> {}")]
+    fn edge(source: &str) -> Result<(u32, u32), TryParseError<()>>;
+
+    #[parser("#{:x}")]
+    fn color(source: &[u8]) -> Option<u32>;
+
+    #[parser("{x}, {y}")]
+    fn point(source: &str) -> (i32, i32);
+
+    #[parser("count: {*count}")]
+    fn count(source: &str, count: &mut u32) -> Result<(), TryParseError<ParseIntError>>;
+
+    #[parser("{(on|off)}")]
+    fn toggle(source: &str) -> Option<()>;
+
+    type Parsed = Result<(u32, u32), TryParseError<()>>;
+
+    #[parser("{}x{}", result)]
+    fn size(source: &str) -> Parsed;
+
+    #[parser("{}x{}", option)]
+    fn area(source: &str) -> std::option::Option<(u32, u32)>;
+
+    #[parser("v{}", panic)]
+    fn version(source: &str) -> u8;
+
+    // Test some general cases that should always pass.
+
+    assert_eq!(edge("1 -> 2"), Ok((1, 2)));
+    assert!(edge("1 <- 2").is_err());
+    assert_eq!(color(b"#ff00ff"), Some(0xff00ff));
+    assert_eq!(color(b"red"), None);
+    assert_eq!(point("1, -2"), (1, -2));
+
+    let mut n = 0;
+    assert_eq!(count("count: 5", &mut n), Ok(()));
+    assert_eq!(n, 5);
+
+    // Test some special cases.
+
+    // Assures parsers without values only check whether the source matches.
+    assert_eq!((toggle("on"), toggle("maybe")), (Some(()), None));
+
+    // Assures the mode may be given when the return type is an alias or a path.
+    assert_eq!((size("2x3"), size("2y3").is_err()), (Ok((2, 3)), true));
+    assert_eq!((area("2x3"), area("2y3")), (Some((2, 3)), None));
+    assert_eq!(version("v2"), 2);
+
+    // Assures parsers returning neither `Result` nor `Option` panic like `scan`.
+    let result = std::panic::catch_unwind(|| point("1; 2"));
+    assert!(result.is_err());
+
+    // Assures the error of a single value keeps the error of its type.
+    assert!(matches!(
+        count("count: x", &mut n),
+        Err(TryParseError::Err(_))
+    ));
+}
+
+mod patterns {
+    crate::pattern!(pub const PAIR = "({}, {})");
+}
+
+#[test]
+fn pattern_args() {
+    use crate::pattern;
+
+    pattern!(const POINT = "{x},{y}");
+    pattern!(const VERSION = concat!("v", 1, ".{}"));
+    pattern!(const MOVE = include_str!("tests/pattern.txt"));
+    pattern!(const FLAG = "{(on|off)}");
+
+    // Test some general cases that should always pass.
+
+    assert_eq!(try_parse!("v1.5" => VERSION), Ok(5));
+    assert_eq!(try_scan!("move 1 2" => MOVE), Ok((1, 2)));
+    assert_eq!(scan!("(3, 4)" => patterns::PAIR), (3, 4));
+    assert_eq!(try_parse!("v1.5" => concat!("v", 1, ".{}")), Ok(5));
+    assert_eq!(try_scan!("1 -> 2" => stringify!({} -> {})), Ok((1, 2)));
+    assert_eq!(
+        try_scan!("move 1 2" => include_str!("tests/pattern.txt")),
+        Ok((1, 2))
+    );
+    assert!(is_match!("on" => FLAG));
+    assert_eq!(parse_or!("v1.x" => VERSION, 0), 0);
+    assert_eq!(unparse!(patterns::PAIR, 1, 2), "(1, 2)");
+    let v = match_str!("v1.2" {
+        concat!("v", 1, ".{n}") => n,
+        _ => 0,
+    });
+    assert_eq!(v, 2);
+
+    // Test some special cases.
+
+    // Assures constants defined through `pattern!` are usable as strings.
+    assert_eq!(
+        (POINT.as_str(), &*VERSION, MOVE.len()),
+        ("{x},{y}", "v1.{}", 10)
+    );
+
+    // Assures named values refer to the variables where the constant is used.
+    let (mut x, mut y) = (0, 0);
+    scan!("1,2" => POINT);
+    assert_eq!((x, y), (1, 2));
+
+    // Assures arguments are passed along with constants.
+    let (mut a, mut b) = (0, 0);
+    try_scan!("(5, 6)" => patterns::PAIR, &mut a, &mut b).unwrap();
+    assert_eq!((a, b), (5, 6));
+}
+
+#[test]
+fn const_parse() {
+    use crate::{const_parse, pattern};
+
+    pattern!(const SIZE = "{}x{}");
+    const WIDTH: usize = const_parse!("width: 640" => "width: {}");
+    const AREA: (u16, u16) = const_parse!("640x480" => SIZE);
+    const RGB: (u8, (u8, u8)) = const_parse!("#ff/00:80" => "#{:x}/{:\"{:x}:{:x}\"}");
+
+    // Test some general cases that should always pass.
+
+    assert_eq!([0u8; WIDTH].len(), 640);
+    assert_eq!(AREA, (640, 480));
+    assert_eq!(RGB, (0xff, (0, 0x80)));
+    assert_eq!(const_parse!("-5" => "{}"), -5);
+    assert_eq!(const_parse!("1.5, -2.5" => "{:f32}, {}"), (1.5f32, -2.5));
+    assert_eq!(const_parse!("mask: 0b1010" => "mask: 0b{:b u8}"), 0b1010);
+    assert_eq!(const_parse!("a" => "a"), ());
+
+    // Test some special cases.
+
+    // Assures skipped values and classes are matched as at runtime.
+    assert_eq!(const_parse!("id: ab12" => "id: {_:alpha}{}"), 12);
+
+    // Assures values out of the range of `i128` are evaluated.
+    assert_eq!(
+        const_parse!("340282366920938463463374607431768211455" => "{}"),
+        u128::MAX
+    );
+
+    // Assures pointer sized values are evaluated for the target's pointer width.
+    assert_eq!(
+        const_parse!("-7, ffff" => "{:isize}, {:x usize}"),
+        (-7, 0xffff)
+    );
+    #[cfg(target_pointer_width = "64")]
+    assert_eq!(
+        const_parse!("5000000000, -5000000000" => "{:usize}, {:isize}"),
+        (5_000_000_000, -5_000_000_000)
+    );
+
+    // Assures floats which aren't finite are evaluated.
+    assert!(const_parse!("inf" => "{:f64}").is_infinite());
+    assert!(const_parse!("NaN" => "{:f32}").is_nan());
+
+    // Assures the sign of negative zero is kept.
+    assert!(const_parse!("-0.0" => "{}").is_sign_negative());
+    assert!(const_parse!("-0" => "{:f32}").is_sign_negative());
+
+    // Assures sources may be included.
+    assert_eq!(
+        const_parse!(concat!("move ", 1, " 2") => include_str!("tests/pattern.txt")),
+        (1, 2)
+    );
+}
+
+#[test]
+fn const_ints() {
+    use crate::{
+        const_parse,
+        int::{parse_binary_i8, parse_hex_u32, parse_i64, parse_u16, parse_usize},
+    };
+    use core::num::IntErrorKind;
+
+    const PORT: u16 = match parse_u16("8080") {
+        Ok(port) => port,
+        Err(_) => panic!("invalid port"),
+    };
+    const MAJOR: u32 = const_parse!(env!("CARGO_PKG_VERSION_MAJOR") => "{}");
+    const VERSION: (u8, u8, u8) = const_parse!(env!("CARGO_PKG_VERSION") => "{}.{}.{}");
+
+    // Test some general cases that should always pass.
+
+    assert_eq!(PORT, 8080);
+    assert_eq!(parse_hex_u32("ff00ff"), Ok(0xff00ff));
+    assert_eq!(parse_binary_i8("-101"), Ok(-5));
+    assert_eq!(parse_i64("+42"), Ok(42));
+    assert_eq!(
+        MAJOR.to_string(),
+        std::env!("CARGO_PKG_VERSION_MAJOR").to_string()
+    );
+    assert_eq!(
+        std::format!("{}.{}.{}", VERSION.0, VERSION.1, VERSION.2),
+        std::env!("CARGO_PKG_VERSION")
+    );
+
+    // Test some special cases.
+
+    // Assures the same text is accepted as by values written as `{}`, `{:x}` and `{:b}`.
+    let kind = |result: Result<usize, core::num::ParseIntError>| *result.unwrap_err().kind();
+    assert_eq!(kind(parse_usize("")), IntErrorKind::Empty);
+    assert_eq!(kind(parse_usize("-1")), IntErrorKind::InvalidDigit);
+    assert_eq!(kind(parse_usize("0x10")), IntErrorKind::InvalidDigit);
+    assert_eq!(
+        parse_hex_u32("0x10").ok(),
+        try_parse!("0x10" => "{:x}").ok()
+    );
+    assert_eq!(
+        parse_u16("65536").unwrap_err().kind(),
+        &IntErrorKind::PosOverflow
+    );
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn heapless() {
+    use crate::stream::{Status, StreamParser};
+    use heapless::{String, Vec};
+
+    // Test some general cases that should always pass.
+
+    assert_eq!(
+        try_parse!("name: ferris" => "name: {}"),
+        Ok(String::<8>::try_from("ferris").unwrap())
+    );
+    let (name, id): (String<16>, u32) = scan!("user ferris#42" => "user {}#{}");
+    assert_eq!((name.as_str(), id), ("ferris", 42));
+    let bytes: Vec<u8, 4> = parse!(b"key=\x01\x02\xff" => "key={}");
+    assert_eq!(bytes, [1, 2, 0xff]);
+
+    let source = String::<16>::try_from("10 rest").unwrap();
+    assert_eq!(try_parse_prefix!(source => "{} "), Ok((10, "rest")));
+
+    let mut parser: StreamParser<(String<4>, u8), 16> = crate::stream_parser!("{}={}\n");
+    assert_eq!(
+        parser.feed(b"led=1\n"),
+        Status::Done((String::try_from("led").unwrap(), 1), 6)
+    );
+
+    // Test some special cases.
+
+    // Assures values which don't fit report the capacity being exceeded.
+    let source = "name: ferris";
+    let name: Result<String<4>, _> = try_parse!(source => "name: {}");
+    assert_eq!(name, Err(TryParseError::CapacityExceeded));
+    assert_eq!(
+        parser.feed(b"speed=1\n"),
+        Status::Error(TryParseError::CapacityExceeded, 8)
+    );
+    // Assures a value which doesn't fit is tried with other splits of the source.
+    let source = "a b c";
+    let (a, b): (String<1>, String<3>) = scan!(source => "{} {}");
+    assert_eq!((a.as_str(), b.as_str()), ("a", "b c"));
+    // Assures invalid utf8 is still rejected.
+    let source: &[u8] = b"\xff";
+    let text: Result<String<4>, _> = try_parse!(source => "{}");
+    assert_eq!(text, Err(TryParseError::InvalidUtf8String));
+    // Assures invalid utf8 is reported over the value not fitting.
+    let source: &[u8] = b"name: ferris\xff";
+    let name: Result<String<4>, _> = try_parse!(source => "name: {}");
+    assert_eq!(name, Err(TryParseError::InvalidUtf8String));
+    let source: &[u8] = b"name: \xe2\x82\xac\xe2\x82";
+    let name: Result<String<2>, _> = try_parse!(source => "name: {}");
+    assert_eq!(name, Err(TryParseError::InvalidUtf8String));
+    let source = "name: \u{20ac}\u{20ac}";
+    let name: Result<String<4>, _> = try_parse!(source => "name: {}");
+    assert_eq!(name, Err(TryParseError::CapacityExceeded));
+}
@@ -0,0 +1,77 @@
+extern crate std;
+
+use crate::__private::buffered;
+use crate::{try_parse, try_scan, TryParseError};
+use strp_core::Memo;
+
+#[test]
+fn memo() {
+    // Test some general cases that should always pass.
+
+    let mut memo = Memo::default();
+    memo.insert(1, 10);
+    assert!(memo.contains(1, 10));
+    assert!(!memo.contains(10, 1));
+
+    // Test some special cases.
+
+    // Assures pairs past the capacity are dropped, without forgetting earlier ones.
+    let mut memo = Memo::default();
+    for pos in 0..100 {
+        memo.insert(0, pos);
+    }
+    assert!((0..32).all(|pos| memo.contains(0, pos)));
+    assert!((32..100).all(|pos| !memo.contains(0, pos)));
+
+    // Assures matching stays correct once the memo is full.
+    let source = "1,".repeat(100);
+    let v: Result<(u8, u8, u8), _> = try_scan!(source => "{},{},{};");
+    assert!(v.is_err());
+    let source = "1,".repeat(100) + "2;";
+    let v: Result<(u8, u8), _> = try_scan!(source => "{},{};");
+    assert!(v.is_err());
+}
+
+#[test]
+fn mismatch() {
+    // Test some general cases that should always pass.
+
+    let source = "x = 10";
+    let v: Result<u32, _> = try_parse!(source => "y = {}");
+    assert_eq!(v, Err(TryParseError::ExpectedMismatch("y = ", 0)));
+    assert_eq!(
+        v.unwrap_err().found(source.as_bytes()),
+        Some(source.as_bytes())
+    );
+
+    let v: Result<(u32, u32), _> = try_scan!("1.2" => "{}.{};");
+    assert_eq!(v, Err(TryParseError::ExpectedMismatch(";", 3)));
+
+    // Test some special cases.
+
+    // Assures only mismatches point into the source.
+    let v: Result<u8, _> = try_parse!("x = 1000" => "x = {}");
+    assert_eq!(v.unwrap_err().found(b"x = 1000"), None);
+}
+
+#[test]
+fn buffer() {
+    // Test some general cases that should always pass.
+
+    let len = buffered::<4, _>(&mut b"1234".iter().copied(), |bytes| bytes.len());
+    assert_eq!(len, Some(4));
+    let source = "(1,2)";
+    assert_eq!(try_parse!(source => r#"{:(u8, u8) "({},{})"}"#), Ok((1, 2)));
+
+    // Test some special cases.
+
+    // Assures values longer than the buffer aren't parsed without an allocator.
+    let len = buffered::<4, _>(&mut b"12345".iter().copied(), |bytes| bytes.len());
+    assert_eq!(len, None);
+    let source = std::format!("({}1,2)", "0".repeat(300));
+    let v: Result<(u16, u8), _> = try_parse!(source => r#"{:(u16, u8) "({},{})"}"#);
+    assert_eq!(v, Err(TryParseError::CapacityExceeded));
+    // Assures floats of any length are parsed without an allocator.
+    let source = std::format!("{}.5{}", "0".repeat(300), "0".repeat(300));
+    assert_eq!(try_parse!(source => "{}"), Ok(0.5f64));
+}